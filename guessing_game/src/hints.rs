@@ -0,0 +1,190 @@
+use crate::i18n::Lang;
+use crate::tr;
+
+/// 可以花分数买的提示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// 上一次猜测离秘密数字有多远
+    Distance,
+    Parity,
+    Divisible(u32),
+    DigitSum,
+}
+
+impl Hint {
+    /// 解析 `hint` 命令后面的部分：hot、parity、div N、digits
+    pub fn parse(text: &str) -> Option<Hint> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match words.as_slice() {
+            ["hot"] | ["cold"] | ["distance"] => Some(Hint::Distance),
+            ["parity"] => Some(Hint::Parity),
+            ["div", n] => match n.parse() {
+                Ok(n) if n >= 2 => Some(Hint::Divisible(n)),
+                _ => None,
+            },
+            ["digits"] => Some(Hint::DigitSum),
+            _ => None,
+        }
+    }
+
+    /// 每个提示扣掉的分数，越直接的提示越贵
+    pub fn cost(self) -> u32 {
+        match self {
+            Hint::Distance => 50,
+            Hint::Divisible(_) => 75,
+            Hint::Parity => 100,
+            Hint::DigitSum => 150,
+        }
+    }
+}
+
+/// 距离按范围宽度的百分比分档
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Band {
+    Burning,
+    Hot,
+    Warm,
+    Cool,
+    Cold,
+}
+
+impl Band {
+    pub fn new(distance: u32, width: u32) -> Band {
+        match distance as u64 * 100 / width.max(1) as u64 {
+            0..=2 => Band::Burning,
+            3..=5 => Band::Hot,
+            6..=15 => Band::Warm,
+            16..=30 => Band::Cool,
+            _ => Band::Cold,
+        }
+    }
+
+    pub fn name(self, lang: Lang) -> String {
+        match self {
+            Band::Burning => tr!(lang, "band.burning"),
+            Band::Hot => tr!(lang, "band.hot"),
+            Band::Warm => tr!(lang, "band.warm"),
+            Band::Cool => tr!(lang, "band.cool"),
+            Band::Cold => tr!(lang, "band.cold"),
+        }
+    }
+}
+
+/// 提示的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clue {
+    Distance { guess: u32, band: Band },
+    Parity { even: bool },
+    Divisible { divisor: u32, divisible: bool },
+    DigitSum(u32),
+}
+
+impl Clue {
+    pub fn new(hint: Hint, secret: u32, last_guess: Option<u32>, width: u32) -> Option<Clue> {
+        Some(match hint {
+            Hint::Distance => {
+                let guess = last_guess?;
+                Clue::Distance {
+                    guess,
+                    band: Band::new(guess.abs_diff(secret), width),
+                }
+            }
+            Hint::Parity => Clue::Parity {
+                even: secret.is_multiple_of(2),
+            },
+            Hint::Divisible(divisor) => Clue::Divisible {
+                divisor,
+                divisible: secret.is_multiple_of(divisor),
+            },
+            Hint::DigitSum => Clue::DigitSum(
+                secret
+                    .to_string()
+                    .chars()
+                    .filter_map(|c| c.to_digit(10))
+                    .sum(),
+            ),
+        })
+    }
+
+    /// 给玩家看的提示内容
+    pub fn text(&self, lang: Lang) -> String {
+        match *self {
+            Clue::Distance { guess, band } => tr!(lang, "clue.distance", guess, band.name(lang)),
+            Clue::Parity { even: true } => tr!(lang, "clue.even"),
+            Clue::Parity { even: false } => tr!(lang, "clue.odd"),
+            Clue::Divisible {
+                divisor,
+                divisible: true,
+            } => tr!(lang, "clue.divisible", divisor),
+            Clue::Divisible {
+                divisor,
+                divisible: false,
+            } => tr!(lang, "clue.not-divisible", divisor),
+            Clue::DigitSum(sum) => tr!(lang, "clue.digit-sum", sum),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintError {
+    /// 距离提示要先猜过一次
+    NoGuessYet,
+    Finished,
+}
+
+/// 赢一局的得分：满分 1000，第一次之后每多猜一次扣 50，再扣提示的花费和每秒 1 分
+pub fn points(attempts: u32, hint_cost: u32, millis: u64) -> u32 {
+    let penalty = 50 * attempts.saturating_sub(1) as u64 + hint_cost as u64 + millis / 1000;
+    1000u64.saturating_sub(penalty) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hints() {
+        assert_eq!(Some(Hint::Distance), Hint::parse("hot"));
+        assert_eq!(Some(Hint::Divisible(3)), Hint::parse(" div  3 "));
+        assert_eq!(None, Hint::parse("div 1"));
+        assert_eq!(None, Hint::parse("div"));
+        assert_eq!(Some(Hint::DigitSum), Hint::parse("digits"));
+        assert_eq!(None, Hint::parse("secret"));
+    }
+
+    #[test]
+    fn clues() {
+        let clue = |hint, last| Clue::new(hint, 472, last, 1000).unwrap().text(Lang::En);
+
+        assert_eq!(
+            "Your last guess 480 is burning",
+            clue(Hint::Distance, Some(480))
+        );
+        assert_eq!(
+            "Your last guess 400 is warm",
+            clue(Hint::Distance, Some(400))
+        );
+        assert_eq!("Your last guess 1 is cold", clue(Hint::Distance, Some(1)));
+        assert_eq!("The number is even", clue(Hint::Parity, None));
+        assert_eq!(
+            "The number is not divisible by 5",
+            clue(Hint::Divisible(5), None)
+        );
+        assert_eq!(
+            "The digits of the number add up to 13",
+            clue(Hint::DigitSum, None)
+        );
+        assert_eq!(None, Clue::new(Hint::Distance, 472, None, 1000));
+
+        let zh = Clue::new(Hint::Distance, 472, Some(480), 1000).unwrap();
+        assert_eq!("你上次猜的 480：烫手了", zh.text(Lang::Zh));
+    }
+
+    #[test]
+    fn points_for_attempts_hints_and_time() {
+        assert_eq!(1000, points(1, 0, 999));
+        assert_eq!(750, points(3, 150, 0));
+        assert_eq!(890, points(2, 50, 10_500));
+        assert_eq!(0, points(30, 0, 0));
+    }
+}
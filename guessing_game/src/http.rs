@@ -0,0 +1,556 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::i18n::Lang;
+use crate::{tr, Config, Game, GuessError, State, Variant};
+
+// 接口：
+//   POST /games                  {"difficulty", "variant", "min", "max", "max_attempts", "seed", "player", "lang"} 都可省略
+//   GET  /games/{id}             查看状态
+//   POST /games/{id}/guesses     {"guess": 42} 或 {"guess": "crane"}
+// 全部返回 JSON，出错时是 {"error": "..."}
+
+/// 请求体的上限，超过时返回 413
+const MAX_BODY: usize = 4 * 1024;
+/// 请求行加请求头的上限
+const MAX_HEAD: u64 = 8 * 1024;
+/// 每个连接的读写超时，防止慢客户端一直占着线程
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Entry {
+    game: Game,
+    seen: Instant,
+}
+
+struct Games {
+    next_id: u64,
+    entries: HashMap<u64, Entry>,
+}
+
+/// 在内存里同时跑多局游戏的 HTTP 服务，超过 `ttl` 没有请求的游戏会被清掉
+pub struct HttpServer {
+    listener: TcpListener,
+    defaults: Config,
+    ttl: Duration,
+    games: Arc<Mutex<Games>>,
+    /// 请求里没给种子时，用它给每局游戏生成不同的种子；启动时的 --seed 只用来初始化它
+    rng: Mutex<StdRng>,
+}
+
+impl HttpServer {
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        defaults: Config,
+        ttl: Duration,
+    ) -> io::Result<HttpServer> {
+        let rng = match defaults.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(HttpServer {
+            listener: TcpListener::bind(addr)?,
+            defaults,
+            rng: Mutex::new(rng),
+            ttl,
+            games: Arc::new(Mutex::new(Games {
+                next_id: 1,
+                entries: HashMap::new(),
+            })),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 一直服务下去，每个连接一个线程
+    pub fn run(self) -> io::Result<()> {
+        let server = Arc::new(self);
+        for stream in server.listener.incoming().flatten() {
+            let server = Arc::clone(&server);
+            thread::spawn(move || {
+                let _ = server.handle(stream);
+            });
+        }
+        Ok(())
+    }
+
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let (status, body) = match read_request(stream) {
+            Ok((method, path, body)) => self.route(&method, &path, &body),
+            Err(err) if err.kind() == io::ErrorKind::FileTooLarge => {
+                error(413, "request body too large")
+            }
+            Err(_) => error(400, "malformed request"),
+        };
+
+        let body = body.to_string();
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason(status),
+            body.len(),
+            body
+        )?;
+        writer.flush()
+    }
+
+    fn route(&self, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut games = self.games.lock().unwrap();
+        let now = Instant::now();
+        games
+            .entries
+            .retain(|_, entry| now.duration_since(entry.seen) < self.ttl);
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("POST", ["games"]) => {
+                let game = match self.create(body) {
+                    Ok(game) => game,
+                    Err(message) => return error(400, &message),
+                };
+                let id = games.next_id;
+                games.next_id += 1;
+                let view = view(id, &game);
+                games.entries.insert(id, Entry { game, seen: now });
+                (201, view)
+            }
+            ("GET", ["games", id]) => match find(&mut games, id, now) {
+                Some(entry) => (200, view(id.parse().unwrap(), &entry.game)),
+                None => error(404, "no such game"),
+            },
+            ("POST", ["games", id, "guesses"]) => {
+                let entry = match find(&mut games, id, now) {
+                    Some(entry) => entry,
+                    None => return error(404, "no such game"),
+                };
+                guess(&mut entry.game, body)
+                    .map(|(feedback, ordering)| {
+                        let mut view = view(id.parse().unwrap(), &entry.game);
+                        view["feedback"] = json!(feedback);
+                        view["correct"] = json!(ordering == Ordering::Equal);
+                        (200, view)
+                    })
+                    .unwrap_or_else(|(status, message)| error(status, &message))
+            }
+            (_, ["games", ..]) => error(405, "method not allowed"),
+            _ => error(404, "not found"),
+        }
+    }
+
+    /// 把请求里的字段拼成命令行参数交给 `Config::new`，和命令行用同一套校验
+    fn create(&self, body: &str) -> Result<Game, String> {
+        let body: Value = if body.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(body).map_err(|err| format!("invalid JSON: {}", err))?
+        };
+
+        let variant = match body.get("variant").and_then(Value::as_str) {
+            None | Some("number") => Variant::Number,
+            Some("code") => Variant::Code(4),
+            Some("word") => Variant::Word,
+            Some(_) => return Err(String::from("variant must be number, code or word")),
+        };
+
+        let mut args = vec![String::from("guessing_game")];
+        args.push(String::from("--name"));
+        args.push(match body.get("player").and_then(Value::as_str) {
+            Some(player) => player.to_string(),
+            None => self.defaults.player.clone(),
+        });
+        // 反馈和解析错误用这局的语言，接口自己的错误信息只有英文
+        let lang = match body.get("lang").and_then(Value::as_str) {
+            Some(name) => Lang::from_name(name).ok_or_else(|| tr!(Lang::En, "args.lang"))?,
+            None => self.defaults.lang,
+        };
+        args.extend([String::from("--lang"), lang.name().to_string()]);
+        // 和命令行一样，密码和单词游戏的范围由玩法决定
+        let number_only = ["difficulty", "min", "max"];
+        if variant != Variant::Number && number_only.iter().any(|f| body.get(f).is_some()) {
+            return Err(tr!(lang, "args.number-only"));
+        }
+        // 请求里没有指定难度和范围时沿用启动服务时的设置
+        let fields = ["difficulty", "min", "max", "max_attempts"];
+        if !fields.iter().any(|field| body.get(field).is_some()) {
+            match self.defaults.difficulty {
+                Some(difficulty) => {
+                    args.extend([String::from("--difficulty"), difficulty.name().to_string()])
+                }
+                None if variant == Variant::Number => args.extend([
+                    String::from("--min"),
+                    self.defaults.min.to_string(),
+                    String::from("--max"),
+                    self.defaults.max.to_string(),
+                    String::from("--max-attempts"),
+                    self.defaults.max_attempts.to_string(),
+                ]),
+                None => args.extend([
+                    String::from("--max-attempts"),
+                    self.defaults.max_attempts.to_string(),
+                ]),
+            }
+        }
+        for (field, option) in [
+            ("difficulty", "--difficulty"),
+            ("min", "--min"),
+            ("max", "--max"),
+            ("max_attempts", "--max-attempts"),
+            ("seed", "--seed"),
+        ] {
+            match body.get(field) {
+                Some(Value::String(value)) => args.extend([option.to_string(), value.clone()]),
+                Some(Value::Number(value)) => args.extend([option.to_string(), value.to_string()]),
+                Some(_) => return Err(format!("{} must be a string or a number", field)),
+                None => {}
+            }
+        }
+        let config = Config::new(args.into_iter()).map_err(|err| tr!(lang, err))?;
+        let config = variant.apply(config);
+
+        let seed = match config.seed {
+            Some(seed) => seed,
+            None => self.rng.lock().unwrap().gen(),
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        Ok(Game::new(&config, &mut rng))
+    }
+}
+
+fn find<'a>(games: &'a mut Games, id: &str, now: Instant) -> Option<&'a mut Entry> {
+    let entry = games.entries.get_mut(&id.parse().ok()?)?;
+    entry.seen = now;
+    Some(entry)
+}
+
+fn guess(game: &mut Game, body: &str) -> Result<(String, Ordering), (u16, String)> {
+    let body: Value =
+        serde_json::from_str(body).map_err(|err| (400, format!("invalid JSON: {}", err)))?;
+    let text = match body.get("guess") {
+        Some(Value::String(text)) => text.trim().to_string(),
+        Some(Value::Number(number)) => number.to_string(),
+        _ => return Err((400, String::from("expected {\"guess\": ...}"))),
+    };
+
+    let (variant, lang) = (game.config().variant, game.config().lang);
+    let guess = variant
+        .parse(&text, lang)
+        .map_err(|message| (422, message))?;
+    match game.guess(guess) {
+        Ok(ordering) => Ok((variant.feedback(guess, game.secret(), lang), ordering)),
+        Err(GuessError::OutOfRange) => Err((
+            422,
+            format!(
+                "the number must be between {} and {}",
+                game.config().min,
+                game.config().max
+            ),
+        )),
+        Err(GuessError::Finished) => Err((409, String::from("the game is over"))),
+    }
+}
+
+/// 游戏状态的 JSON，结束之后才带上秘密
+fn view(id: u64, game: &Game) -> Value {
+    let config = game.config();
+    let variant = config.variant;
+    let history: Vec<Value> = game
+        .history()
+        .iter()
+        .map(|attempt| {
+            json!({
+                "guess": variant.display(attempt.guess),
+                "feedback": variant.feedback(attempt.guess, game.secret(), config.lang),
+            })
+        })
+        .collect();
+
+    let mut view = json!({
+        "id": id,
+        "variant": variant.name(),
+        "player": config.player,
+        "min": config.min,
+        "max": config.max,
+        "max_attempts": config.max_attempts,
+        "attempts": game.attempts(),
+        "attempts_left": game.attempts_left(),
+        "state": match game.state() {
+            State::Playing => "playing",
+            State::Won => "won",
+            State::Lost => "lost",
+        },
+        "points": game.points(),
+        "history": history,
+    });
+    if game.state() != State::Playing {
+        view["secret"] = json!(variant.display(game.secret()));
+    }
+    view
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+/// 只支持这个服务用得到的那部分 HTTP/1.1：请求行、Content-Length 和请求体
+///
+/// 请求体超过 `MAX_BODY` 时返回 `FileTooLarge`
+fn read_request(stream: TcpStream) -> io::Result<(String, String, String)> {
+    let mut reader = BufReader::new(stream.take(MAX_HEAD));
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(io::ErrorKind::InvalidData.into());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            // 连接断了，或者请求头超过了 MAX_HEAD
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+            }
+        }
+    }
+
+    if length > MAX_BODY {
+        return Err(io::ErrorKind::FileTooLarge.into());
+    }
+    reader.get_mut().set_limit(length as u64);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    Ok((method, path, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(ttl: Duration) -> SocketAddr {
+        serve(crate::test_config(1, 100, 10), ttl)
+    }
+
+    fn serve(defaults: Config, ttl: Duration) -> SocketAddr {
+        let server = HttpServer::bind("127.0.0.1:0", defaults, ttl).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn secret(seed: u64) -> u32 {
+        let config = crate::test_config(1, 100, 10);
+        Game::new(&config, &mut StdRng::seed_from_u64(seed)).secret()
+    }
+
+    #[test]
+    fn create_guess_and_fetch() {
+        let addr = server(Duration::from_secs(60));
+        let secret = secret(21);
+        let wrong = if secret == 1 { 2 } else { 1 };
+
+        let (status, game) = request(addr, "POST", "/games", r#"{"seed": 21}"#);
+        assert_eq!(201, status);
+        assert_eq!(100, game["max"]);
+        assert_eq!("playing", game["state"]);
+        assert!(game.get("secret").is_none());
+        let id = game["id"].as_u64().unwrap();
+
+        let path = format!("/games/{}/guesses", id);
+        let (status, body) = request(addr, "POST", &path, &format!(r#"{{"guess": {}}}"#, wrong));
+        assert_eq!(200, status);
+        assert_eq!("is smaller", body["feedback"]);
+        assert_eq!(9, body["attempts_left"]);
+
+        let (status, body) = request(addr, "POST", &path, r#"{"guess": 500}"#);
+        assert_eq!(422, status);
+        assert_eq!("the number must be between 1 and 100", body["error"]);
+
+        let (_, body) = request(
+            addr,
+            "POST",
+            &path,
+            &format!(r#"{{"guess": "{}"}}"#, secret),
+        );
+        assert_eq!(true, body["correct"]);
+        assert_eq!("won", body["state"]);
+
+        let (status, body) = request(addr, "GET", &format!("/games/{}", id), "");
+        assert_eq!(200, status);
+        assert_eq!(secret.to_string(), body["secret"]);
+        assert_eq!(2, body["history"].as_array().unwrap().len());
+
+        let (status, _) = request(addr, "POST", &path, r#"{"guess": 1}"#);
+        assert_eq!(409, status);
+    }
+
+    #[test]
+    fn concurrent_games_are_independent() {
+        let addr = server(Duration::from_secs(60));
+
+        let clients: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(move || {
+                    let (_, game) = request(
+                        addr,
+                        "POST",
+                        "/games",
+                        r#"{"variant": "word", "player": "p"}"#,
+                    );
+                    let id = game["id"].as_u64().unwrap();
+                    let path = format!("/games/{}/guesses", id);
+                    let (status, body) = request(addr, "POST", &path, r#"{"guess": "crane"}"#);
+                    assert_eq!(200, status);
+                    assert_eq!(1, body["attempts"]);
+                    id
+                })
+            })
+            .collect();
+        let mut ids: Vec<u64> = clients.into_iter().map(|c| c.join().unwrap()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(8, ids.len());
+    }
+
+    #[test]
+    fn idle_games_expire() {
+        let addr = server(Duration::from_millis(100));
+        let (_, game) = request(addr, "POST", "/games", "");
+        let path = format!("/games/{}", game["id"]);
+
+        assert_eq!(200, request(addr, "GET", &path, "").0);
+        thread::sleep(Duration::from_millis(250));
+        assert_eq!(404, request(addr, "GET", &path, "").0);
+    }
+
+    #[test]
+    fn bad_requests() {
+        let addr = server(Duration::from_secs(60));
+
+        assert_eq!(404, request(addr, "GET", "/nope", "").0);
+        assert_eq!(404, request(addr, "GET", "/games/999", "").0);
+        assert_eq!(405, request(addr, "DELETE", "/games", "").0);
+        assert_eq!(400, request(addr, "POST", "/games", "{").0);
+        let (_, game) = request(addr, "POST", "/games", r#"{"variant": "code"}"#);
+        let path = format!("/games/{}/guesses", game["id"]);
+        let (status, body) = request(addr, "POST", &path, r#"{"guess": "12"}"#);
+        assert_eq!(422, status);
+        assert_eq!("'12' is not a 4-digit code", body["error"]);
+        assert_eq!(400, request(addr, "POST", &path, r#"{"answer": 1}"#).0);
+        let (status, body) = request(addr, "POST", "/games", r#"{"difficulty": "extreme"}"#);
+        assert_eq!(400, status);
+        assert_eq!("Difficulty must be easy, normal or hard", body["error"]);
+        let (status, body) = request(addr, "POST", "/games", r#"{"variant": "word", "max": 50}"#);
+        assert_eq!(400, status);
+        assert_eq!(
+            "--min, --max and --difficulty only apply to the number game",
+            body["error"]
+        );
+    }
+
+    #[test]
+    fn oversized_bodies_are_rejected() {
+        let addr = server(Duration::from_secs(60));
+
+        // 只发请求头，声明一个很大的请求体，服务端不应该等着读或者按它分配内存
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /games HTTP/1.1\r\nContent-Length: 1000000000\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+
+        // 正好在上限以内的请求体照常处理
+        let body = format!(r#"{{"player": "{}"}}"#, "x".repeat(MAX_BODY - 14));
+        assert_eq!(MAX_BODY, body.len());
+        assert_eq!(201, request(addr, "POST", "/games", &body).0);
+    }
+
+    /// 最大次数为 1，猜一次就结束，返回揭晓的秘密
+    fn finish(addr: SocketAddr) -> String {
+        let (_, game) = request(addr, "POST", "/games", r#"{"max_attempts": 1}"#);
+        let path = format!("/games/{}/guesses", game["id"]);
+        let (_, body) = request(addr, "POST", &path, r#"{"guess": 1}"#);
+        body["secret"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn server_seed_gives_each_game_its_own_secret() {
+        let seeded = || {
+            let defaults = Config {
+                seed: Some(7),
+                ..crate::test_config(1, 100, 10)
+            };
+            serve(defaults, Duration::from_secs(60))
+        };
+
+        let addr = seeded();
+        let secrets: Vec<String> = (0..5).map(|_| finish(addr)).collect();
+        assert!(secrets.iter().any(|secret| *secret != secrets[0]));
+
+        // 同一个种子启动的服务按同样的顺序出题
+        let addr = seeded();
+        let again: Vec<String> = (0..5).map(|_| finish(addr)).collect();
+        assert_eq!(secrets, again);
+    }
+}
@@ -0,0 +1,496 @@
+use std::env;
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Zh,
+}
+
+impl Lang {
+    /// 接受 en、zh，也接受 zh_CN.UTF-8 这种 `LANG` 的写法
+    pub fn from_name(name: &str) -> Option<Lang> {
+        let name = name.to_ascii_lowercase();
+        if name.starts_with("zh") {
+            Some(Lang::Zh)
+        } else if name.starts_with("en") || name == "c" || name == "posix" {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Zh => "zh",
+        }
+    }
+
+    /// 没有 `--lang` 时看 `LANG`，都没有或不认识时用英文
+    pub fn from_env() -> Lang {
+        env::var("LANG")
+            .ok()
+            .and_then(|lang| Lang::from_name(&lang))
+            .unwrap_or(Lang::En)
+    }
+
+    /// 参数解析失败前也要知道用什么语言报错，所以直接在原始参数里找 `--lang`
+    pub fn detect(args: &[String]) -> Lang {
+        args.iter()
+            .position(|arg| arg == "--lang")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|name| Lang::from_name(name))
+            .unwrap_or_else(Lang::from_env)
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::Zh => ZH,
+        }
+    }
+}
+
+/// 按 `lang` 取出 `key` 对应的消息，把 `{0}`、`{1}`…… 换成 `args`
+pub fn tr(lang: Lang, key: &str, args: &[&dyn fmt::Display]) -> String {
+    let template = lang
+        .catalog()
+        .iter()
+        .chain(EN)
+        .find(|(k, _)| *k == key)
+        .map(|(_, message)| *message);
+    debug_assert!(template.is_some(), "missing message {}", key);
+
+    // 从左到右扫一遍模板，填进去的值里即使有 `{1}` 也不会再被替换
+    let mut rest = template.unwrap_or(key);
+    let mut message = String::new();
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let arg = rest
+            .find('}')
+            .and_then(|end| Some((end, args.get(rest[1..end].parse::<usize>().ok()?)?)));
+        match arg {
+            Some((end, arg)) => {
+                let _ = write!(message, "{}", arg);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                message.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+/// `tr!(lang, "key", a, b)` 等于 `tr(lang, "key", &[&a, &b])`
+#[macro_export]
+macro_rules! tr {
+    ($lang:expr, $key:expr $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::tr($lang, $key, &[$(&$arg as &dyn ::std::fmt::Display),*])
+    };
+}
+
+const EN: &[(&str, &str)] = &[
+    ("args.missing-value", "Missing option value"),
+    ("args.unknown-option", "Unknown option"),
+    ("args.difficulty", "Difficulty must be easy, normal or hard"),
+    ("args.strategy", "Strategy must be binary, random or linear"),
+    ("args.lang", "Language must be en or zh"),
+    ("args.invalid-min", "Invalid number for --min"),
+    ("args.invalid-max", "Invalid number for --max"),
+    (
+        "args.invalid-max-attempts",
+        "Invalid number for --max-attempts",
+    ),
+    ("args.invalid-seed", "Invalid number for --seed"),
+    ("args.invalid-port", "Invalid number for --port"),
+    ("args.invalid-games", "Invalid number for --games"),
+    ("args.invalid-ttl", "Invalid number for --ttl"),
+    ("args.invalid-length", "Invalid number for --length"),
+    ("args.min-max", "--min must not be greater than --max"),
+    ("args.attempts-zero", "--max-attempts must be at least 1"),
+    ("args.length", "--length must be between 2 and 9"),
+    (
+        "args.number-only",
+        "--min, --max and --difficulty only apply to the number game",
+    ),
+    ("args.usage-replay", "Usage: guessing_game replay FILE"),
+    (
+        "args.usage-join",
+        "Usage: guessing_game join HOST:PORT [--name NAME]",
+    ),
+    ("noun.number", "number"),
+    ("noun.code", "code"),
+    ("noun.word", "word"),
+    ("board.easy", "easy"),
+    ("board.normal", "normal"),
+    ("board.hard", "hard"),
+    ("board.custom", "custom"),
+    ("board.code", "code"),
+    ("board.code-length", "code ({0} digits)"),
+    ("board.word", "word"),
+    ("play.title", "~~Guessing Game~~"),
+    ("play.prompt", "Please input a {0}"),
+    ("play.bye", "Bye! The secret {0} was {1}"),
+    ("play.help", "Type a {0} to guess it, or one of:"),
+    ("play.help-history", "  history   show your guesses so far"),
+    (
+        "play.help-hint",
+        "  hint ...  hint hot, hint parity, hint div N, hint digits",
+    ),
+    ("play.help-quit", "  quit      give up and reveal the {0}"),
+    ("play.no-history", "No guesses yet"),
+    (
+        "play.hint-number-only",
+        "Hints are only available in the number game",
+    ),
+    (
+        "play.hint-usage",
+        "Hints: hint hot, hint parity, hint div N, hint digits",
+    ),
+    ("play.hint-cost", "{0} (-{1} points)"),
+    (
+        "play.hint-no-guess",
+        "Make a guess before asking how close it is",
+    ),
+    ("play.parse-error", "{0}, type help to see the commands"),
+    (
+        "play.out-of-range",
+        "The number must be between {0} and {1}",
+    ),
+    ("play.win", "~~win win win~~"),
+    ("play.score", "Score: {0} points"),
+    ("play.game-over", "~~game over~~ The secret {0} was {1}"),
+    ("play.attempts-left", "{0} attempts left"),
+    ("feedback.smaller", "is smaller"),
+    ("feedback.bigger", "is bigger"),
+    ("feedback.correct", "is correct"),
+    ("feedback.bulls-cows", "{0} bulls, {1} cows"),
+    (
+        "intro.number",
+        "Guess a number between {0} and {1}, you have {2} attempts",
+    ),
+    (
+        "intro.code",
+        "Guess the {0}-digit code (digits may repeat), you have {1} attempts\n\
+         Bulls are right digits in the right place, cows are right digits in the wrong place",
+    ),
+    (
+        "intro.word",
+        "Guess the five-letter word, you have {0} attempts\n\
+         + right letter in the right place, ? right letter in the wrong place, - not in the word",
+    ),
+    ("parse.number", "'{0}' is not a number"),
+    ("parse.code", "'{0}' is not a {1}-digit code"),
+    ("parse.word", "'{0}' is not in the word list"),
+    ("clue.distance", "Your last guess {0} is {1}"),
+    ("band.burning", "burning"),
+    ("band.hot", "hot"),
+    ("band.warm", "warm"),
+    ("band.cool", "cool"),
+    ("band.cold", "cold"),
+    ("clue.even", "The number is even"),
+    ("clue.odd", "The number is odd"),
+    ("clue.divisible", "The number is divisible by {0}"),
+    ("clue.not-divisible", "The number is not divisible by {0}"),
+    ("clue.digit-sum", "The digits of the number add up to {0}"),
+    ("reverse.title", "~~Reverse Guessing Game~~"),
+    (
+        "reverse.intro",
+        "Think of a number between {0} and {1}, I have {2} attempts",
+    ),
+    ("reverse.ask", "Is it {0}? (higher/lower/correct)"),
+    (
+        "reverse.answer-usage",
+        "Please answer higher, lower or correct",
+    ),
+    (
+        "reverse.lie-higher",
+        "That contradicts your earlier answer: you said the number is higher than {0}",
+    ),
+    (
+        "reverse.lie-lower",
+        "That contradicts your earlier answer: you said the number is lower than {0}",
+    ),
+    (
+        "reverse.lie-range",
+        "That contradicts the range: the number must be between {0} and {1}",
+    ),
+    (
+        "reverse.win",
+        "~~I win~~ Your number is {0}, found in {1} attempts",
+    ),
+    (
+        "reverse.give-up",
+        "~~I give up~~ Your number is somewhere between {0} and {1}",
+    ),
+    ("autoplay.guess", "Guess {0}: {1}"),
+    (
+        "autoplay.stats",
+        "{0} strategy: won {1}/{2} games, attempts min {3} / mean {4} / max {5}",
+    ),
+    ("join.welcome", "Joined! Guess a number between {0} and {1}"),
+    ("join.guess", "{0} guessed {1}: {2}"),
+    ("join.win", "~~{0} wins~~ The secret number was {1}"),
+    ("join.expected-name", "The server expected NAME <name>"),
+    ("join.expected-guess", "Please type a number"),
+    ("state.won", "won"),
+    ("state.lost", "lost"),
+    ("state.abandoned", "abandoned"),
+    (
+        "replay.secret-mismatch",
+        "Replay mismatch: recorded secret {0}, replayed secret {1}",
+    ),
+    (
+        "replay.rejected",
+        "Replay mismatch: guess {0} was rejected ({1})",
+    ),
+    (
+        "replay.outcome-mismatch",
+        "Replay mismatch: recorded {0}, replayed {1}",
+    ),
+    (
+        "replay.matches",
+        "Replay matches: {0} after {1} attempts, secret {2}",
+    ),
+    ("main.args-error", "Problem parsing arguments: {0}"),
+    ("main.app-error", "Application error: {0}"),
+    ("main.recorded", "Session recorded to {0} (seed {1})"),
+    ("main.no-scores", "No scores yet"),
+    ("main.board", "== {0} =="),
+    ("main.score-line", "{0}. {1} {2} points {3} attempts {4}s"),
+    ("main.serving", "Serving a round between {0} and {1} on {2}"),
+    (
+        "main.connection-closed",
+        "Connection closed before anyone won",
+    ),
+    (
+        "main.http",
+        "Serving the JSON API on http://{0} (games expire after {1}s idle)",
+    ),
+];
+
+const ZH: &[(&str, &str)] = &[
+    ("args.missing-value", "选项缺少值"),
+    ("args.unknown-option", "未知选项"),
+    ("args.difficulty", "难度必须是 easy、normal 或 hard"),
+    ("args.strategy", "策略必须是 binary、random 或 linear"),
+    ("args.lang", "语言必须是 en 或 zh"),
+    ("args.invalid-min", "--min 不是有效的数字"),
+    ("args.invalid-max", "--max 不是有效的数字"),
+    ("args.invalid-max-attempts", "--max-attempts 不是有效的数字"),
+    ("args.invalid-seed", "--seed 不是有效的数字"),
+    ("args.invalid-port", "--port 不是有效的数字"),
+    ("args.invalid-games", "--games 不是有效的数字"),
+    ("args.invalid-ttl", "--ttl 不是有效的数字"),
+    ("args.invalid-length", "--length 不是有效的数字"),
+    ("args.min-max", "--min 不能大于 --max"),
+    ("args.attempts-zero", "--max-attempts 至少是 1"),
+    ("args.length", "--length 必须在 2 到 9 之间"),
+    (
+        "args.number-only",
+        "--min、--max 和 --difficulty 只能用于猜数字",
+    ),
+    ("args.usage-replay", "用法：guessing_game replay 文件"),
+    (
+        "args.usage-join",
+        "用法：guessing_game join 主机:端口 [--name 名字]",
+    ),
+    ("noun.number", "数字"),
+    ("noun.code", "密码"),
+    ("noun.word", "单词"),
+    ("board.easy", "简单"),
+    ("board.normal", "普通"),
+    ("board.hard", "困难"),
+    ("board.custom", "自定义"),
+    ("board.code", "猜密码"),
+    ("board.code-length", "猜密码（{0} 位）"),
+    ("board.word", "猜单词"),
+    ("play.title", "~~猜数字游戏~~"),
+    ("play.prompt", "请输入一个{0}"),
+    ("play.bye", "再见！秘密{0}是 {1}"),
+    ("play.help", "输入一个{0}来猜，或者输入下面的命令："),
+    ("play.help-history", "  history   查看猜过的记录"),
+    (
+        "play.help-hint",
+        "  hint ...  hint hot、hint parity、hint div N、hint digits",
+    ),
+    ("play.help-quit", "  quit      放弃并揭晓{0}"),
+    ("play.no-history", "还没有猜过"),
+    ("play.hint-number-only", "只有猜数字游戏才有提示"),
+    (
+        "play.hint-usage",
+        "提示：hint hot、hint parity、hint div N、hint digits",
+    ),
+    ("play.hint-cost", "{0}（扣 {1} 分）"),
+    ("play.hint-no-guess", "先猜一次才能问离得有多近"),
+    ("play.parse-error", "{0}，输入 help 查看命令"),
+    ("play.out-of-range", "数字必须在 {0} 到 {1} 之间"),
+    ("play.win", "~~赢了赢了~~"),
+    ("play.score", "得分：{0} 分"),
+    ("play.game-over", "~~游戏结束~~ 秘密{0}是 {1}"),
+    ("play.attempts-left", "还剩 {0} 次机会"),
+    ("feedback.smaller", "小了"),
+    ("feedback.bigger", "大了"),
+    ("feedback.correct", "猜对了"),
+    ("feedback.bulls-cows", "{0} 个位置正确，{1} 个位置不对"),
+    (
+        "intro.number",
+        "猜一个 {0} 到 {1} 之间的数字，你有 {2} 次机会",
+    ),
+    (
+        "intro.code",
+        "猜一个 {0} 位密码（数字可以重复），你有 {1} 次机会\n\
+         位置正确：数字和位置都对；位置不对：数字对但位置不对",
+    ),
+    (
+        "intro.word",
+        "猜一个五个字母的英文单词，你有 {0} 次机会\n\
+         + 字母和位置都对，? 字母对但位置不对，- 单词里没有这个字母",
+    ),
+    ("parse.number", "“{0}”不是数字"),
+    ("parse.code", "“{0}”不是 {1} 位密码"),
+    ("parse.word", "“{0}”不在单词表里"),
+    ("clue.distance", "你上次猜的 {0}：{1}"),
+    ("band.burning", "烫手了"),
+    ("band.hot", "很热"),
+    ("band.warm", "有点暖"),
+    ("band.cool", "有点凉"),
+    ("band.cold", "冰冷"),
+    ("clue.even", "这个数是偶数"),
+    ("clue.odd", "这个数是奇数"),
+    ("clue.divisible", "这个数能被 {0} 整除"),
+    ("clue.not-divisible", "这个数不能被 {0} 整除"),
+    ("clue.digit-sum", "这个数的各位数字之和是 {0}"),
+    ("reverse.title", "~~反过来猜数字~~"),
+    (
+        "reverse.intro",
+        "心里想一个 {0} 到 {1} 之间的数字，我有 {2} 次机会",
+    ),
+    ("reverse.ask", "是 {0} 吗？（higher/lower/correct）"),
+    ("reverse.answer-usage", "请回答 higher、lower 或 correct"),
+    (
+        "reverse.lie-higher",
+        "这和你之前的回答矛盾：你说过这个数比 {0} 大",
+    ),
+    (
+        "reverse.lie-lower",
+        "这和你之前的回答矛盾：你说过这个数比 {0} 小",
+    ),
+    (
+        "reverse.lie-range",
+        "这和范围矛盾：这个数必须在 {0} 到 {1} 之间",
+    ),
+    ("reverse.win", "~~我赢了~~ 你想的是 {0}，我猜了 {1} 次"),
+    ("reverse.give-up", "~~我放弃了~~ 你想的数在 {0} 到 {1} 之间"),
+    ("autoplay.guess", "猜 {0}：{1}"),
+    (
+        "autoplay.stats",
+        "{0} 策略：赢了 {1}/{2} 局，次数 最少 {3} / 平均 {4} / 最多 {5}",
+    ),
+    ("join.welcome", "已加入！猜一个 {0} 到 {1} 之间的数字"),
+    ("join.guess", "{0} 猜了 {1}：{2}"),
+    ("join.win", "~~{0} 赢了~~ 秘密数字是 {1}"),
+    ("join.expected-name", "服务器要求先发送 NAME <名字>"),
+    ("join.expected-guess", "请输入一个数字"),
+    ("state.won", "赢了"),
+    ("state.lost", "输了"),
+    ("state.abandoned", "中途放弃"),
+    (
+        "replay.secret-mismatch",
+        "回放不一致：记录的秘密是 {0}，重放得到 {1}",
+    ),
+    ("replay.rejected", "回放不一致：猜测 {0} 被拒绝（{1}）"),
+    (
+        "replay.outcome-mismatch",
+        "回放不一致：记录的结果是{0}，重放的结果是{1}",
+    ),
+    ("replay.matches", "回放一致：猜了 {1} 次后{0}，秘密是 {2}"),
+    ("main.args-error", "参数有误：{0}"),
+    ("main.app-error", "程序出错：{0}"),
+    ("main.recorded", "对局已记录到 {0}（种子 {1}）"),
+    ("main.no-scores", "还没有成绩"),
+    ("main.board", "== {0} =="),
+    ("main.score-line", "{0}. {1} {2} 分 {3} 次 {4} 秒"),
+    ("main.serving", "在 {2} 上开始一轮，数字在 {0} 到 {1} 之间"),
+    ("main.connection-closed", "还没有人赢连接就断开了"),
+    (
+        "main.http",
+        "JSON 接口运行在 http://{0}（游戏闲置 {1} 秒后过期）",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &str) -> BTreeSet<usize> {
+        (0..10)
+            .filter(|i| message.contains(&format!("{{{}}}", i)))
+            .collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_locale() {
+        let locales = [Lang::En, Lang::Zh];
+
+        for lang in locales {
+            let keys: BTreeSet<&str> = lang.catalog().iter().map(|(key, _)| *key).collect();
+            assert_eq!(
+                lang.catalog().len(),
+                keys.len(),
+                "duplicate key in {:?}",
+                lang
+            );
+
+            for other in locales {
+                for (key, message) in other.catalog() {
+                    let translated = lang
+                        .catalog()
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .unwrap_or_else(|| panic!("{:?} is missing {}", lang, key));
+                    assert_eq!(
+                        placeholders(message),
+                        placeholders(translated.1),
+                        "{} has different placeholders in {:?}",
+                        key,
+                        lang
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lang_names() {
+        assert_eq!(Some(Lang::Zh), Lang::from_name("zh_CN.UTF-8"));
+        assert_eq!(Some(Lang::Zh), Lang::from_name("zh"));
+        assert_eq!(Some(Lang::En), Lang::from_name("en_US.UTF-8"));
+        assert_eq!(Some(Lang::En), Lang::from_name("C"));
+        assert_eq!(None, Lang::from_name("fr_FR"));
+
+        let args = ["guessing_game", "--lang", "zh"].map(String::from);
+        assert_eq!(Lang::Zh, Lang::detect(&args));
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_order() {
+        assert_eq!("Score: 42 points", tr!(Lang::En, "play.score", 42));
+        assert_eq!(
+            "在 127.0.0.1:1 上开始一轮，数字在 1 到 9 之间",
+            tr!(Lang::Zh, "main.serving", 1, 9, "127.0.0.1:1")
+        );
+        // 填进去的值不会再被当成占位符
+        assert_eq!(
+            " 1. {2}  7 points  3 attempts 1.5s",
+            tr!(Lang::En, "main.score-line", " 1", "{2}", " 7", " 3", "1.5")
+        );
+        assert_eq!("== {x} ==", tr!(Lang::En, "main.board", "{x}"));
+    }
+}
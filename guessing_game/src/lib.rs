@@ -0,0 +1,1059 @@
+use rand::Rng;
+use std::cmp::Ordering;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod hints;
+mod http;
+pub mod i18n;
+mod net;
+mod replay;
+mod reverse;
+mod scores;
+mod solver;
+mod variants;
+
+pub use hints::{Band, Clue, Hint, HintError};
+pub use http::HttpServer;
+pub use i18n::Lang;
+pub use net::{join, round_config, Server};
+pub use replay::Session;
+pub use reverse::{reverse, Verdict};
+pub use scores::{leaderboard, Score, ScoreStore};
+pub use solver::{autoplay, solve, Contradiction, Solver, Stats, Strategy};
+pub use variants::{bulls_and_cows, letter_marks, words, Variant};
+
+pub enum Command {
+    Play(Config),
+    Replay(String, Lang),
+    Scores(Lang),
+    Serve(Config, u16),
+    Join(String, Config),
+    Autoplay(Config, Strategy, u32),
+    Reverse(Config, Strategy),
+    /// 配置、监听地址、端口和空闲游戏的存活秒数
+    Http(Config, String, u16, u64),
+}
+
+impl Command {
+    /// 出错时返回 `i18n` 里消息的键
+    pub fn new(args: impl Iterator<Item = String>) -> Result<Command, &'static str> {
+        let args: Vec<String> = args.collect();
+
+        match args.as_slice() {
+            [_, command, file, rest @ ..] if command == "replay" && !file.starts_with("--") => {
+                Ok(Command::Replay(file.clone(), lang_option(rest)?))
+            }
+            [_, command, ..] if command == "replay" => Err("args.usage-replay"),
+            [_, command, rest @ ..] if command == "scores" => {
+                Ok(Command::Scores(lang_option(rest)?))
+            }
+            [program, command, rest @ ..] if command == "serve" => {
+                let (options, extra) = split_options(program, rest, &["--port"])?;
+                let port = match extra.last() {
+                    Some((_, port)) => port.parse().map_err(|_| "args.invalid-port")?,
+                    None => 7878,
+                };
+                Config::new(options.into_iter()).map(|config| Command::Serve(config, port))
+            }
+            [program, command, rest @ ..] if command == "autoplay" => {
+                let (options, extra) = split_options(program, rest, &["--strategy", "--games"])?;
+                let mut strategy = Strategy::Binary;
+                let mut games = 1;
+                for (name, value) in extra {
+                    if name == "--strategy" {
+                        strategy = Strategy::from_name(&value).ok_or("args.strategy")?;
+                    } else {
+                        games = value.parse().map_err(|_| "args.invalid-games")?;
+                    }
+                }
+                Config::new(options.into_iter())
+                    .map(|config| Command::Autoplay(config, strategy, games))
+            }
+            [program, command, rest @ ..] if command == "http" => {
+                let (options, extra) =
+                    split_options(program, rest, &["--host", "--port", "--ttl"])?;
+                // 默认只听本机，要对外提供服务时显式给出 --host 0.0.0.0
+                let mut host = String::from("127.0.0.1");
+                let mut port = 8080;
+                let mut ttl = 600;
+                for (name, value) in extra {
+                    match name.as_str() {
+                        "--host" => host = value,
+                        "--port" => port = value.parse().map_err(|_| "args.invalid-port")?,
+                        _ => ttl = value.parse().map_err(|_| "args.invalid-ttl")?,
+                    }
+                }
+                Config::new(options.into_iter())
+                    .map(|config| Command::Http(config, host, port, ttl))
+            }
+            [program, command, rest @ ..] if command == "code" => {
+                let (options, extra) = split_options(program, rest, &["--length"])?;
+                let length = match extra.last() {
+                    Some((_, length)) => length.parse().map_err(|_| "args.invalid-length")?,
+                    None => 4,
+                };
+                if !(2..=9).contains(&length) {
+                    return Err("args.length");
+                }
+                number_only(&options[1..])?;
+                Config::new(options.into_iter())
+                    .map(|config| Command::Play(Variant::Code(length).apply(config)))
+            }
+            [program, command, rest @ ..] if command == "word" => {
+                number_only(rest)?;
+                let options = std::iter::once(program.clone()).chain(rest.iter().cloned());
+                Config::new(options).map(|config| Command::Play(Variant::Word.apply(config)))
+            }
+            [program, command, rest @ ..] if command == "reverse" => {
+                let (options, extra) = split_options(program, rest, &["--strategy"])?;
+                let strategy = match extra.last() {
+                    Some((_, name)) => Strategy::from_name(name).ok_or("args.strategy")?,
+                    None => Strategy::Binary,
+                };
+                Config::new(options.into_iter()).map(|config| Command::Reverse(config, strategy))
+            }
+            [program, command, addr, rest @ ..] if command == "join" => {
+                let options = std::iter::once(program.clone()).chain(rest.iter().cloned());
+                Config::new(options).map(|config| Command::Join(addr.clone(), config))
+            }
+            [_, command, ..] if command == "join" => Err("args.usage-join"),
+            _ => Config::new(args.into_iter()).map(Command::Play),
+        }
+    }
+}
+
+/// 密码和单词游戏的范围和次数由玩法决定，只能用 `--max-attempts` 自定义次数，
+/// 数字游戏的范围和难度选项会被忽略，干脆报错
+fn number_only(options: &[String]) -> Result<(), &'static str> {
+    // 选项都带一个值，只看选项名的位置
+    let names = options.iter().step_by(2);
+    if names
+        .into_iter()
+        .any(|name| ["--min", "--max", "--difficulty"].contains(&name.as_str()))
+    {
+        return Err("args.number-only");
+    }
+    Ok(())
+}
+
+/// 交给 `Config::new` 的参数，和子命令自己的 (选项, 值)
+type SplitOptions = (Vec<String>, Vec<(String, String)>);
+
+/// 把子命令自己的选项 `names` 从参数里拿出来，剩下的交给 `Config::new`
+fn split_options(
+    program: &str,
+    rest: &[String],
+    names: &[&str],
+) -> Result<SplitOptions, &'static str> {
+    let mut options = vec![program.to_string()];
+    let mut extra = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        if names.contains(&arg.as_str()) {
+            let value = rest.next().ok_or("args.missing-value")?;
+            extra.push((arg.clone(), value.clone()));
+        } else {
+            options.push(arg.clone());
+        }
+    }
+
+    Ok((options, extra))
+}
+
+/// 只接受 `--lang` 的子命令（replay、scores）用
+fn lang_option(rest: &[String]) -> Result<Lang, &'static str> {
+    match rest {
+        [] => Ok(Lang::from_env()),
+        [option, name] if option == "--lang" => Lang::from_name(name).ok_or("args.lang"),
+        [option] if option == "--lang" => Err("args.missing-value"),
+        _ => Err("args.unknown-option"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    // (min, max, max_attempts)
+    pub fn preset(self) -> (u32, u32, u32) {
+        match self {
+            Difficulty::Easy => (1, 50, 12),
+            Difficulty::Normal => (1, 100, 10),
+            Difficulty::Hard => (1, 1000, 10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub min: u32,
+    pub max: u32,
+    pub max_attempts: u32,
+    pub seed: Option<u64>,
+    pub record: Option<PathBuf>,
+    /// 自定义了范围或次数时为 `None`，不计入对应难度的排行榜
+    pub difficulty: Option<Difficulty>,
+    pub player: String,
+    pub variant: Variant,
+    /// 提示和反馈用的语言
+    pub lang: Lang,
+}
+
+impl Config {
+    /// 出错时返回 `i18n` 里消息的键
+    pub fn new(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
+        args.next();
+
+        let mut difficulty = Difficulty::Normal;
+        let mut min = None;
+        let mut max = None;
+        let mut max_attempts = None;
+        let mut seed = None;
+        let mut record = None;
+        let mut player = None;
+        let mut lang = None;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or("args.missing-value");
+            match arg.as_str() {
+                "--difficulty" => {
+                    difficulty = Difficulty::from_name(&value()?).ok_or("args.difficulty")?;
+                }
+                "--min" => min = Some(value()?.parse().map_err(|_| "args.invalid-min")?),
+                "--max" => max = Some(value()?.parse().map_err(|_| "args.invalid-max")?),
+                "--max-attempts" => {
+                    max_attempts = Some(value()?.parse().map_err(|_| "args.invalid-max-attempts")?);
+                }
+                "--seed" => seed = Some(value()?.parse().map_err(|_| "args.invalid-seed")?),
+                "--record" => record = Some(PathBuf::from(value()?)),
+                "--name" => player = Some(value()?),
+                "--lang" => lang = Some(Lang::from_name(&value()?).ok_or("args.lang")?),
+                _ => return Err("args.unknown-option"),
+            }
+        }
+
+        // 显式给出的选项覆盖难度预设
+        let (preset_min, preset_max, preset_attempts) = difficulty.preset();
+        let custom = min.is_some() || max.is_some() || max_attempts.is_some();
+        let player = player
+            .or_else(|| env::var("USER").ok())
+            .or_else(|| env::var("USERNAME").ok())
+            .unwrap_or_else(|| String::from("anonymous"));
+        let config = Config {
+            min: min.unwrap_or(preset_min),
+            max: max.unwrap_or(preset_max),
+            max_attempts: max_attempts.unwrap_or(preset_attempts),
+            seed,
+            record,
+            difficulty: if custom { None } else { Some(difficulty) },
+            player,
+            variant: Variant::Number,
+            lang: lang.unwrap_or_else(Lang::from_env),
+        };
+
+        if config.min > config.max {
+            return Err("args.min-max");
+        }
+        if config.max_attempts == 0 {
+            return Err("args.attempts-zero");
+        }
+
+        Ok(config)
+    }
+
+    /// 成绩记在哪个排行榜：数字游戏按难度，密码按位数（如 `code-4`），
+    /// 自定义了范围或次数的都是 `None`
+    pub fn board(&self) -> Option<String> {
+        match self.variant {
+            Variant::Number => self.difficulty.map(|d| d.name().to_string()),
+            variant if Some(self.max_attempts) != variant.default_attempts() => None,
+            Variant::Code(length) => Some(format!("code-{}", length)),
+            Variant::Word => Some(String::from("word")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuessError {
+    OutOfRange,
+    Finished,
+}
+
+/// 一次计入次数的猜测，`at` 是 Unix 毫秒时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attempt {
+    pub guess: u32,
+    pub at: u64,
+}
+
+/// 一局游戏的状态机：Playing --guess--> Playing / Won / Lost
+pub struct Game {
+    config: Config,
+    secret: u32,
+    started: u64,
+    history: Vec<Attempt>,
+    hints: Vec<Hint>,
+    state: State,
+}
+
+impl Game {
+    pub fn new(config: &Config, rng: &mut impl Rng) -> Game {
+        Game::with_secret(config, rng.gen_range(config.min..=config.max))
+    }
+
+    pub fn with_secret(config: &Config, secret: u32) -> Game {
+        Game {
+            config: config.clone(),
+            secret,
+            started: now_millis(),
+            history: Vec::new(),
+            hints: Vec::new(),
+            state: State::Playing,
+        }
+    }
+
+    /// 返回猜测相对于秘密数字的大小，超出范围的猜测不计入次数
+    pub fn guess(&mut self, guess: u32) -> Result<Ordering, GuessError> {
+        if self.state != State::Playing {
+            return Err(GuessError::Finished);
+        }
+        if guess < self.config.min || guess > self.config.max {
+            return Err(GuessError::OutOfRange);
+        }
+
+        self.history.push(Attempt {
+            guess,
+            at: now_millis(),
+        });
+        let ordering = guess.cmp(&self.secret);
+        if ordering == Ordering::Equal {
+            self.state = State::Won;
+        } else if self.attempts() == self.config.max_attempts {
+            self.state = State::Lost;
+        }
+
+        Ok(ordering)
+    }
+
+    /// 买一个提示，花费在赢的时候从得分里扣掉
+    pub fn hint(&mut self, hint: Hint) -> Result<Clue, HintError> {
+        if self.state != State::Playing {
+            return Err(HintError::Finished);
+        }
+
+        let last_guess = self.history.last().map(|attempt| attempt.guess);
+        let width = (self.config.max - self.config.min).saturating_add(1);
+        let clue = Clue::new(hint, self.secret, last_guess, width).ok_or(HintError::NoGuessYet)?;
+        self.hints.push(hint);
+
+        Ok(clue)
+    }
+
+    pub fn hints(&self) -> &[Hint] {
+        &self.hints
+    }
+
+    /// 赢了才有分，综合猜的次数、提示花费和用时
+    pub fn points(&self) -> u32 {
+        if self.state != State::Won {
+            return 0;
+        }
+        let hint_cost = self.hints.iter().map(|hint| hint.cost()).sum();
+        hints::points(self.attempts(), hint_cost, self.elapsed_millis())
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.history.len() as u32
+    }
+
+    pub fn attempts_left(&self) -> u32 {
+        self.config.max_attempts - self.attempts()
+    }
+
+    pub fn history(&self) -> &[Attempt] {
+        &self.history
+    }
+
+    /// 从开局到最后一次猜测经过的毫秒数
+    pub fn elapsed_millis(&self) -> u64 {
+        self.history
+            .last()
+            .map(|attempt| attempt.at.saturating_sub(self.started))
+            .unwrap_or(0)
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 存放回放、分数等文件的目录，可以用 `GUESSING_GAME_DATA` 覆盖
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("GUESSING_GAME_DATA") {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join("guessing_game");
+    }
+    if let Some(dir) = env::var_os("APPDATA") {
+        return PathBuf::from(dir).join("guessing_game");
+    }
+    match env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".local/share/guessing_game"),
+        None => PathBuf::from(".guessing_game"),
+    }
+}
+
+/// 从 `input` 读取猜测、把提示写到 `output`，直到游戏结束、玩家 quit 或输入结束
+pub fn play(
+    config: &Config,
+    rng: &mut impl Rng,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<Game> {
+    let mut game = Game::new(config, rng);
+
+    let (variant, lang) = (config.variant, config.lang);
+    let noun = variant.noun(lang);
+
+    writeln!(output, "{}", tr!(lang, "play.title"))?;
+    writeln!(output, "{}", variant.intro(config))?;
+
+    while game.state() == State::Playing {
+        writeln!(output, "{}", tr!(lang, "play.prompt", noun))?;
+        let mut line = String::new();
+        // 输入结束（Ctrl-D 或管道读完）和 quit 一样，不算输也不算赢
+        if input.read_line(&mut line)? == 0 {
+            let secret = variant.display(game.secret());
+            writeln!(output, "{}", tr!(lang, "play.bye", noun, secret))?;
+            break;
+        }
+
+        let line = line.trim();
+        match line {
+            "quit" | "exit" => {
+                let secret = variant.display(game.secret());
+                writeln!(output, "{}", tr!(lang, "play.bye", noun, secret))?;
+                break;
+            }
+            "help" => {
+                writeln!(output, "{}", tr!(lang, "play.help", noun))?;
+                writeln!(output, "{}", tr!(lang, "play.help-history"))?;
+                if variant == Variant::Number {
+                    writeln!(output, "{}", tr!(lang, "play.help-hint"))?;
+                }
+                writeln!(output, "{}", tr!(lang, "play.help-quit", noun))?;
+                continue;
+            }
+            "history" => {
+                if game.history().is_empty() {
+                    writeln!(output, "{}", tr!(lang, "play.no-history"))?;
+                }
+                for (i, attempt) in game.history().iter().enumerate() {
+                    writeln!(
+                        output,
+                        "{:>2}. {} {}",
+                        i + 1,
+                        variant.display(attempt.guess),
+                        variant.feedback(attempt.guess, game.secret(), lang)
+                    )?;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(rest) = line.strip_prefix("hint") {
+            if variant != Variant::Number {
+                writeln!(output, "{}", tr!(lang, "play.hint-number-only"))?;
+                continue;
+            }
+            let clue = match Hint::parse(rest) {
+                Some(hint) => game.hint(hint).map(|clue| (clue, hint.cost())),
+                None => {
+                    writeln!(output, "{}", tr!(lang, "play.hint-usage"))?;
+                    continue;
+                }
+            };
+            match clue {
+                Ok((clue, cost)) => writeln!(
+                    output,
+                    "{}",
+                    tr!(lang, "play.hint-cost", clue.text(lang), cost)
+                )?,
+                Err(_) => writeln!(output, "{}", tr!(lang, "play.hint-no-guess"))?,
+            }
+            continue;
+        }
+
+        let guess = match variant.parse(line, lang) {
+            Ok(guess) => guess,
+            Err(err) => {
+                writeln!(output, "{}", tr!(lang, "play.parse-error", err))?;
+                continue;
+            }
+        };
+
+        let ordering = match game.guess(guess) {
+            Ok(ordering) => ordering,
+            Err(GuessError::OutOfRange) => {
+                writeln!(
+                    output,
+                    "{}",
+                    tr!(lang, "play.out-of-range", config.min, config.max)
+                )?;
+                continue;
+            }
+            Err(GuessError::Finished) => unreachable!("the loop stops once the game is over"),
+        };
+        match (variant, ordering) {
+            (Variant::Number, Ordering::Equal) => {}
+            (Variant::Number, _) => {
+                writeln!(output, "{}", variant.feedback(guess, game.secret(), lang))?
+            }
+            _ => writeln!(
+                output,
+                "{} {}",
+                variant.display(guess),
+                variant.feedback(guess, game.secret(), lang)
+            )?,
+        }
+        if ordering == Ordering::Equal {
+            writeln!(output, "{}", tr!(lang, "play.win"))?;
+            writeln!(output, "{}", tr!(lang, "play.score", game.points()))?;
+        }
+
+        match game.state() {
+            State::Lost => {
+                let secret = variant.display(game.secret());
+                writeln!(output, "{}", tr!(lang, "play.game-over", noun, secret))?
+            }
+            State::Playing => writeln!(
+                output,
+                "{}",
+                tr!(lang, "play.attempts-left", game.attempts_left())
+            )?,
+            State::Won => {}
+        }
+    }
+
+    Ok(game)
+}
+
+/// 各模块测试共用的自定义配置：数字游戏、英文、没有种子和记录
+#[cfg(test)]
+pub(crate) fn test_config(min: u32, max: u32, max_attempts: u32) -> Config {
+    Config {
+        min,
+        max,
+        max_attempts,
+        seed: None,
+        record: None,
+        difficulty: None,
+        player: String::from("tester"),
+        variant: Variant::Number,
+        lang: Lang::En,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn secret_for(config: &Config, seed: u64) -> u32 {
+        Game::new(config, &mut StdRng::seed_from_u64(seed)).secret()
+    }
+
+    fn play_script(config: &Config, seed: u64, script: &str) -> (Game, String) {
+        let mut output = Vec::new();
+        let game = play(
+            config,
+            &mut StdRng::seed_from_u64(seed),
+            script.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        (game, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn presets_and_overrides() {
+        let args = [
+            "guessing_game",
+            "--difficulty",
+            "hard",
+            "--max-attempts",
+            "3",
+            "--name",
+            "tester",
+            "--lang",
+            "en",
+        ];
+        let config = Config::new(args.map(String::from).into_iter()).unwrap();
+
+        assert_eq!(test_config(1, 1000, 3), config);
+
+        let args = ["guessing_game", "--difficulty", "easy"];
+        let config = Config::new(args.map(String::from).into_iter()).unwrap();
+        assert_eq!(Some(Difficulty::Easy), config.difficulty);
+    }
+
+    #[test]
+    fn serve_and_join_commands() {
+        let args = [
+            "guessing_game",
+            "serve",
+            "--port",
+            "9000",
+            "--name",
+            "tester",
+        ];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Serve(config, port) => {
+                assert_eq!(9000, port);
+                assert_eq!("tester", config.player);
+            }
+            _ => panic!("expected serve"),
+        }
+
+        let args = [
+            "guessing_game",
+            "join",
+            "localhost:7878",
+            "--name",
+            "tester",
+        ];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Join(addr, config) => {
+                assert_eq!("localhost:7878", addr);
+                assert_eq!("tester", config.player);
+            }
+            _ => panic!("expected join"),
+        }
+    }
+
+    #[test]
+    fn http_command() {
+        let args = ["guessing_game", "http", "--ttl", "30"];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Http(_, host, port, ttl) => {
+                assert_eq!(("127.0.0.1", 8080, 30), (host.as_str(), port, ttl));
+            }
+            _ => panic!("expected http"),
+        }
+
+        let args = ["guessing_game", "http", "--host", "0.0.0.0", "--port", "80"];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Http(_, host, port, _) => assert_eq!(("0.0.0.0", 80), (host.as_str(), port)),
+            _ => panic!("expected http"),
+        }
+    }
+
+    #[test]
+    fn autoplay_command() {
+        let args = [
+            "guessing_game",
+            "autoplay",
+            "--difficulty",
+            "hard",
+            "--strategy",
+            "linear",
+            "--games",
+            "50",
+        ];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Autoplay(config, strategy, games) => {
+                assert_eq!(1000, config.max);
+                assert_eq!(Strategy::Linear, strategy);
+                assert_eq!(50, games);
+            }
+            _ => panic!("expected autoplay"),
+        }
+
+        let args = ["guessing_game", "autoplay", "--strategy", "psychic"];
+        assert!(Command::new(args.map(String::from).into_iter()).is_err());
+    }
+
+    #[test]
+    fn variant_commands() {
+        let args = ["guessing_game", "code", "--length", "3", "--name", "tester"];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Play(config) => {
+                assert_eq!(Variant::Code(3), config.variant);
+                assert_eq!((0, 999), (config.min, config.max));
+            }
+            _ => panic!("expected code"),
+        }
+
+        let args = ["guessing_game", "word", "--max-attempts", "8"];
+        match Command::new(args.map(String::from).into_iter()).unwrap() {
+            Command::Play(config) => {
+                assert_eq!(Variant::Word, config.variant);
+                assert_eq!(8, config.max_attempts);
+            }
+            _ => panic!("expected word"),
+        }
+
+        let args = ["guessing_game", "code", "--length", "12"];
+        assert!(Command::new(args.map(String::from).into_iter()).is_err());
+
+        // 范围和难度只属于数字游戏，给了也不会生效，所以直接拒绝
+        for args in [
+            &["word", "--max", "50"][..],
+            &["word", "--name", "tester", "--difficulty", "hard"],
+            &["code", "--min", "1"],
+            &["code", "--length", "3", "--max", "50"],
+        ] {
+            let args = std::iter::once("guessing_game").chain(args.iter().copied());
+            assert!(matches!(
+                Command::new(args.map(String::from)),
+                Err("args.number-only")
+            ));
+        }
+        // 名字恰好长得像选项时不算
+        let args = ["guessing_game", "word", "--name", "--max"];
+        assert!(Command::new(args.map(String::from).into_iter()).is_ok());
+    }
+
+    #[test]
+    fn score_boards() {
+        let board = |args: &[&str]| {
+            let args = std::iter::once("guessing_game").chain(args.iter().copied());
+            match Command::new(args.map(String::from)).unwrap() {
+                Command::Play(config) => config.board(),
+                _ => panic!("expected a game"),
+            }
+        };
+
+        assert_eq!(Some(String::from("normal")), board(&[]));
+        assert_eq!(Some(String::from("hard")), board(&["--difficulty", "hard"]));
+        assert_eq!(None, board(&["--max", "50"]));
+        assert_eq!(Some(String::from("code-4")), board(&["code"]));
+        assert_eq!(
+            Some(String::from("code-3")),
+            board(&["code", "--length", "3"])
+        );
+        assert_eq!(None, board(&["code", "--max-attempts", "20"]));
+        assert_eq!(Some(String::from("word")), board(&["word"]));
+        assert_eq!(None, board(&["word", "--max-attempts", "8"]));
+        // 显式给出和默认一样的次数不算自定义
+        assert_eq!(
+            Some(String::from("word")),
+            board(&["word", "--max-attempts", "6"])
+        );
+        // 不会因为给了无效的 --max 被记到自定义榜
+        let args = ["guessing_game", "word", "--max", "50"];
+        assert!(matches!(
+            Command::new(args.map(String::from).into_iter()),
+            Err("args.number-only")
+        ));
+    }
+
+    #[test]
+    fn code_game_shares_the_loop() {
+        let config = Variant::Code(3).apply(test_config(1, 100, 10));
+        let secret = secret_for(&config, 2);
+        let wrong = (secret + 1) % 1000;
+
+        let script = format!("12\n{:03}\nhint parity\nhistory\n{:03}\n", wrong, secret);
+        let (game, output) = play_script(&config, 2, &script);
+
+        assert_eq!(State::Won, game.state());
+        assert_eq!(2, game.attempts());
+        assert!(output.contains("Please input a code\n"));
+        assert!(output.contains("'12' is not a 3-digit code, type help to see the commands\n"));
+        assert!(output.contains("Hints are only available in the number game\n"));
+        assert!(output.contains(&format!(" 1. {:03} ", wrong)));
+        assert!(output.contains(&format!("{:03} 3 bulls, 0 cows\n~~win win win~~\n", secret)));
+    }
+
+    #[test]
+    fn word_game_lost_reveals_word() {
+        let config = Variant::Word.apply(Config {
+            max_attempts: 1,
+            ..test_config(1, 100, 1)
+        });
+        let secret = secret_for(&config, 6);
+        let wrong = if secret == 0 { "above" } else { "about" };
+
+        let (game, output) = play_script(&config, 6, &format!("{}\n", wrong));
+
+        assert_eq!(State::Lost, game.state());
+        assert!(output.ends_with(&format!(
+            "~~game over~~ The secret word was {}\n",
+            Variant::Word.display(secret)
+        )));
+    }
+
+    #[test]
+    fn chinese_messages() {
+        let config = Config {
+            lang: Lang::Zh,
+            ..test_config(1, 100, 2)
+        };
+        let secret = secret_for(&config, 3);
+        let low = if secret == 1 { 2 } else { 1 };
+
+        let (_, output) = play_script(&config, 3, &format!("abc\n{}\n{}\n", low, low));
+
+        assert!(output.starts_with("~~猜数字游戏~~\n猜一个 1 到 100 之间的数字，你有 2 次机会\n"));
+        assert!(output.contains("“abc”不是数字，输入 help 查看命令\n"));
+        assert!(output.ends_with(&format!("~~游戏结束~~ 秘密数字是 {}\n", secret)));
+
+        let args = ["guessing_game", "scores", "--lang", "zh"];
+        assert!(matches!(
+            Command::new(args.map(String::from).into_iter()),
+            Ok(Command::Scores(Lang::Zh))
+        ));
+        let args = ["guessing_game", "--lang", "fr"];
+        assert_eq!(
+            Err("args.lang"),
+            Config::new(args.map(String::from).into_iter())
+        );
+    }
+
+    #[test]
+    fn invalid_range() {
+        let args = ["guessing_game", "--min", "10", "--max", "5"];
+
+        assert_eq!(
+            Err("args.min-max"),
+            Config::new(args.map(String::from).into_iter())
+        );
+    }
+
+    fn parse(args: &[&str]) -> Result<Config, &'static str> {
+        let args = std::iter::once("guessing_game").chain(args.iter().copied());
+        Config::new(args.map(String::from))
+    }
+
+    fn limits(config: &Config) -> (u32, u32, u32, Option<Difficulty>) {
+        (
+            config.min,
+            config.max,
+            config.max_attempts,
+            config.difficulty,
+        )
+    }
+
+    #[test]
+    fn presets_apply_without_overrides() {
+        let normal = parse(&[]).unwrap();
+        assert_eq!((1, 100, 10, Some(Difficulty::Normal)), limits(&normal));
+
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let config = parse(&["--difficulty", difficulty.name()]).unwrap();
+            let (min, max, attempts) = difficulty.preset();
+            assert_eq!((min, max, attempts, Some(difficulty)), limits(&config));
+        }
+    }
+
+    #[test]
+    fn explicit_options_override_the_preset() {
+        // 只覆盖给出的那一项，其余仍取预设，但不再算作这个难度
+        let config = parse(&["--difficulty", "easy", "--max", "20"]).unwrap();
+        assert_eq!((1, 20, 12, None), limits(&config));
+
+        let config = parse(&["--min", "50", "--difficulty", "hard"]).unwrap();
+        assert_eq!((50, 1000, 10, None), limits(&config));
+
+        let config = parse(&["--difficulty", "hard", "--max-attempts", "1"]).unwrap();
+        assert_eq!((1, 1000, 1, None), limits(&config));
+    }
+
+    #[test]
+    fn min_above_max_is_rejected() {
+        assert_eq!(Err("args.min-max"), parse(&["--min", "101"]));
+        // 只改 max 也可能比预设的 min 还小
+        assert_eq!(Err("args.min-max"), parse(&["--max", "0"]));
+        let config = parse(&["--min", "7", "--max", "7"]).unwrap();
+        assert_eq!((7, 7, 10, None), limits(&config));
+    }
+
+    #[test]
+    fn zero_attempts_are_rejected() {
+        assert_eq!(Err("args.attempts-zero"), parse(&["--max-attempts", "0"]));
+        assert_eq!(
+            Err("args.attempts-zero"),
+            parse(&["--difficulty", "easy", "--max-attempts", "0"])
+        );
+        assert_eq!(1, parse(&["--max-attempts", "1"]).unwrap().max_attempts);
+        assert_eq!(
+            Err("args.invalid-max-attempts"),
+            parse(&["--max-attempts", "-1"])
+        );
+    }
+
+    #[test]
+    fn state_machine() {
+        let mut game = Game::with_secret(&test_config(1, 10, 2), 7);
+
+        assert_eq!(Err(GuessError::OutOfRange), game.guess(11));
+        assert_eq!(0, game.attempts());
+        assert_eq!(Ok(Ordering::Less), game.guess(3));
+        assert_eq!(State::Playing, game.state());
+        assert_eq!(Ok(Ordering::Equal), game.guess(7));
+        assert_eq!(State::Won, game.state());
+        assert_eq!(Err(GuessError::Finished), game.guess(7));
+    }
+
+    #[test]
+    fn losing_when_attempts_run_out() {
+        let mut game = Game::with_secret(&test_config(1, 10, 2), 7);
+
+        assert_eq!(Ok(Ordering::Greater), game.guess(9));
+        assert_eq!(Ok(Ordering::Greater), game.guess(8));
+        assert_eq!(State::Lost, game.state());
+    }
+
+    #[test]
+    fn seeded_games_are_repeatable() {
+        let config = test_config(1, 100, 10);
+
+        assert_eq!(secret_for(&config, 42), secret_for(&config, 42));
+    }
+
+    #[test]
+    fn full_game_won_by_binary_search() {
+        let config = test_config(1, 100, 10);
+        let secret = secret_for(&config, 7);
+
+        // 根据秘密数字预先算出二分查找会输入的每一步
+        let (mut low, mut high) = (1, 100);
+        let mut script = String::from("oops\n");
+        loop {
+            let mid = (low + high) / 2;
+            script.push_str(&format!("{}\n", mid));
+            match mid.cmp(&secret) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid - 1,
+                Ordering::Equal => break,
+            }
+        }
+
+        let (game, output) = play_script(&config, 7, &script);
+        assert_eq!(State::Won, game.state());
+        assert!(game.attempts() <= 7);
+        assert!(output.contains("~~win win win~~\nScore: "));
+        assert_eq!(1000 - 50 * (game.attempts() - 1), game.points());
+    }
+
+    #[test]
+    fn hints_cost_points() {
+        let config = test_config(1, 100, 10);
+        let secret = secret_for(&config, 11);
+        let wrong = if secret == 1 { 2 } else { 1 };
+
+        let script = format!(
+            "hint hot\nhint\n{}\nhint hot\nhint parity\n{}\n",
+            wrong, secret
+        );
+        let (game, output) = play_script(&config, 11, &script);
+
+        assert!(output.contains("Make a guess before asking how close it is\n"));
+        assert!(output.contains("Hints: hint hot, hint parity, hint div N, hint digits\n"));
+        assert!(output.contains(&format!("Your last guess {} is ", wrong)));
+        assert!(output.contains(" (-100 points)\n"));
+        assert_eq!(&[Hint::Distance, Hint::Parity], game.hints());
+        assert_eq!(2, game.attempts());
+        assert_eq!(1000 - 50 - 150, game.points());
+        assert_eq!(
+            Err(HintError::NoGuessYet),
+            Game::with_secret(&config, 5).hint(Hint::Distance)
+        );
+    }
+
+    #[test]
+    fn commands_and_parse_errors() {
+        let config = test_config(1, 100, 10);
+        let secret = secret_for(&config, 5);
+        let wrong = if secret == 1 { 2 } else { 1 };
+
+        let script = format!("history\nhelp\nfifty\n{}\nhistory\nquit\n", wrong);
+        let (game, output) = play_script(&config, 5, &script);
+
+        assert_eq!(State::Playing, game.state());
+        assert_eq!(1, game.attempts());
+        assert!(output.contains("No guesses yet\n"));
+        assert!(output.contains("  quit      give up and reveal the number\n"));
+        assert!(output.contains("'fifty' is not a number, type help to see the commands\n"));
+        assert!(output.contains(&format!(" 1. {} is smaller\n", wrong)));
+        assert!(output.ends_with(&format!("Bye! The secret number was {}\n", secret)));
+    }
+
+    #[test]
+    fn eof_ends_the_game() {
+        let config = test_config(1, 100, 10);
+
+        let secret = secret_for(&config, 5);
+
+        let (game, output) = play_script(&config, 5, "");
+        assert_eq!(State::Playing, game.state());
+        assert_eq!(0, game.attempts());
+        assert!(output.ends_with(&format!(
+            "Please input a number\nBye! The secret number was {}\n",
+            secret
+        )));
+    }
+
+    #[test]
+    fn full_game_lost_reveals_secret() {
+        let config = test_config(1, 100, 2);
+        let secret = secret_for(&config, 3);
+        let wrong = if secret == 1 { 2 } else { 1 };
+
+        let script = format!("0\n{}\n{}\n", wrong, wrong);
+        let (game, output) = play_script(&config, 3, &script);
+
+        assert_eq!(State::Lost, game.state());
+        assert!(output.contains("The number must be between 1 and 100\n"));
+        assert!(output.ends_with(&format!("~~game over~~ The secret number was {}\n", secret)));
+    }
+}
@@ -1,30 +1,196 @@
-use rand::Rng;
-use std::cmp::Ordering;
+use guessing_game::{
+    tr, Command, Config, Game, HttpServer, Lang, Score, ScoreStore, Server, Session, State,
+    Strategy,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::env;
+use std::error::Error;
 use std::io; // prelude // trait
+use std::path::Path;
+use std::process;
+use std::time::Duration;
 
 fn main() {
-    println!("~~Guessing Game~~");
-
-    let secret_number = rand::thread_rng().gen_range(1..101); // i32 u32 i64
-
-    loop {
-        println!("Please input a number");
-        let mut guess = String::new();
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Cannot read input!"); // io::Result Ok/Err
-
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("is smaller"),
-            Ordering::Greater => println!("is bigger"),
-            Ordering::Equal => {
-                println!("~~win win win~~");
-                break;
-            }
+    let args: Vec<String> = env::args().collect();
+    let lang = Lang::detect(&args);
+    let command = Command::new(args.into_iter()).unwrap_or_else(|err| {
+        eprintln!("{}", tr!(lang, "main.args-error", tr!(lang, err)));
+        process::exit(1);
+    });
+
+    let result = match command {
+        Command::Play(config) => play(config),
+        Command::Replay(file, lang) => replay(&file, lang),
+        Command::Scores(lang) => scores(lang),
+        Command::Serve(config, port) => serve(config, port),
+        Command::Join(addr, config) => join(&addr, &config),
+        Command::Autoplay(config, strategy, games) => autoplay(&config, strategy, games),
+        Command::Reverse(config, strategy) => reverse(&config, strategy),
+        Command::Http(config, host, port, ttl) => http(config, &host, port, ttl),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", tr!(lang, "main.app-error", err));
+        process::exit(1);
+    }
+}
+
+fn play(config: Config) -> Result<(), Box<dyn Error>> {
+    // 没有指定种子时也随机生成一个，这样每一局都能重放
+    let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let game = guessing_game::play(&config, &mut rng, io::stdin().lock(), io::stdout())?;
+
+    let session = Session::new(seed, &game);
+    let path = config
+        .record
+        .clone()
+        .unwrap_or_else(|| session.default_path());
+    session.save(&path)?;
+    println!(
+        "{}",
+        tr!(config.lang, "main.recorded", path.display(), seed)
+    );
+
+    if game.state() == State::Won {
+        ScoreStore::open_default().add(&Score {
+            player: config.player.clone(),
+            difficulty: config.board(),
+            attempts: game.attempts(),
+            millis: game.elapsed_millis(),
+            points: game.points(),
+            at: guessing_game::now_millis(),
+        })?;
+    }
+
+    Ok(())
+}
+
+fn scores(lang: Lang) -> Result<(), Box<dyn Error>> {
+    let scores = ScoreStore::open_default().load()?;
+    if scores.is_empty() {
+        println!("{}", tr!(lang, "main.no-scores"));
+        return Ok(());
+    }
+
+    // 旧版本把所有密码游戏都记在 "code" 下，仍然单独列出来
+    let codes: Vec<String> = (2..=9).map(|length| format!("code-{}", length)).collect();
+    let boards = ["easy", "normal", "hard"]
+        .into_iter()
+        .map(Some)
+        .chain([None, Some("code")])
+        .chain(codes.iter().map(|code| Some(code.as_str())))
+        .chain([Some("word")]);
+    for difficulty in boards {
+        let board = guessing_game::leaderboard(&scores, difficulty);
+        if board.is_empty() {
+            continue;
+        }
+
+        println!("{}", tr!(lang, "main.board", board_name(lang, difficulty)));
+        for (rank, score) in board.iter().take(10).enumerate() {
+            println!(
+                "{}",
+                tr!(
+                    lang,
+                    "main.score-line",
+                    format!("{:>2}", rank + 1),
+                    format!("{:<16}", score.player),
+                    format!("{:>4}", score.points),
+                    format!("{:>3}", score.attempts),
+                    format!("{:>8.1}", score.millis as f64 / 1000.0)
+                )
+            );
         }
     }
+
+    Ok(())
+}
+
+/// 排行榜的标题，`None` 是自定义范围或次数的成绩
+fn board_name(lang: Lang, board: Option<&str>) -> String {
+    if let Some(length) = board.and_then(|board| board.strip_prefix("code-")) {
+        return tr!(lang, "board.code-length", length);
+    }
+    let key = match board {
+        Some("easy") => "board.easy",
+        Some("normal") => "board.normal",
+        Some("hard") => "board.hard",
+        Some("code") => "board.code",
+        Some("word") => "board.word",
+        _ => "board.custom",
+    };
+    tr!(lang, key)
+}
+
+fn serve(config: Config, port: u16) -> Result<(), Box<dyn Error>> {
+    let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let config = guessing_game::round_config(&config);
+    let game = Game::new(&config, &mut StdRng::seed_from_u64(seed));
+
+    let server = Server::bind(("0.0.0.0", port), game)?;
+    println!(
+        "{}",
+        tr!(
+            config.lang,
+            "main.serving",
+            config.min,
+            config.max,
+            server.local_addr()?
+        )
+    );
+    let (winner, secret) = server.run()?;
+    println!("{}", tr!(config.lang, "join.win", winner, secret));
+
+    Ok(())
+}
+
+fn join(addr: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    if guessing_game::join(
+        addr,
+        &config.player,
+        config.lang,
+        io::BufReader::new(io::stdin()),
+        io::stdout(),
+    )?
+    .is_none()
+    {
+        return Err(tr!(config.lang, "main.connection-closed").into());
+    }
+
+    Ok(())
+}
+
+fn autoplay(config: &Config, strategy: Strategy, games: u32) -> Result<(), Box<dyn Error>> {
+    let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    guessing_game::autoplay(config, strategy, games, &mut rng, io::stdout())?;
+
+    Ok(())
+}
+
+fn reverse(config: &Config, strategy: Strategy) -> Result<(), Box<dyn Error>> {
+    let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    guessing_game::reverse(config, strategy, &mut rng, io::stdin().lock(), io::stdout())?;
+
+    Ok(())
+}
+
+fn http(config: Config, host: &str, port: u16, ttl: u64) -> Result<(), Box<dyn Error>> {
+    let lang = config.lang;
+    let server = HttpServer::bind((host, port), config, Duration::from_secs(ttl))?;
+    println!("{}", tr!(lang, "main.http", server.local_addr()?, ttl));
+    server.run()?;
+
+    Ok(())
+}
+
+fn replay(file: &str, lang: Lang) -> Result<(), Box<dyn Error>> {
+    let session = Session::load(Path::new(file))?;
+    session.replay(lang, io::stdout())?;
+
+    Ok(())
 }
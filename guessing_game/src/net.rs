@@ -0,0 +1,361 @@
+use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::i18n::Lang;
+use crate::{tr, Config, Game, GuessError, State};
+
+// 协议是一行一条的文本：
+//   客户端 -> 服务器：NAME <name>，GUESS <n>
+//   服务器 -> 客户端：WELCOME <min> <max>，GUESS <name> <n> <smaller|bigger|correct>，
+//                     ERROR <code> [args]，WIN <name> <secret>
+//   ERROR 只带错误码和参数，由客户端翻译：expected-name，expected-guess，range <min> <max>
+
+/// 写一行超过这个时间还没写完，就当客户端已经卡死并断开它
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Client {
+    /// 发给这个客户端的每一行都经过它交给写线程，不会和别的消息交错
+    lines: mpsc::Sender<String>,
+    /// 结束时用来关掉读的一端，让处理它的线程退出
+    stream: TcpStream,
+}
+
+struct Round {
+    game: Game,
+    clients: Vec<Client>,
+    winner: Option<String>,
+}
+
+impl Round {
+    /// 只是放进各个客户端的队列，不会在持有锁时阻塞在网络写上
+    fn broadcast(&mut self, line: &str) {
+        // 写线程已经退出的客户端已经断开，直接丢掉
+        self.clients
+            .retain(|client| client.lines.send(line.to_string()).is_ok());
+    }
+}
+
+/// 给客户端开一个写线程，写失败或超时就断开连接，之后往返回的发送端里发消息会失败
+fn spawn_writer(mut stream: TcpStream) -> io::Result<mpsc::Sender<String>> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (sender, lines) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in lines {
+            if writeln!(stream, "{}", line).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+    Ok(sender)
+}
+
+/// 一轮多人游戏：所有人猜同一个秘密数字，第一个猜中的人获胜
+pub struct Server {
+    listener: TcpListener,
+    round: Arc<Mutex<Round>>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, game: Game) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            round: Arc::new(Mutex::new(Round {
+                game,
+                clients: Vec::new(),
+                winner: None,
+            })),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 接受连接直到有人猜中，返回获胜者的名字和秘密数字
+    pub fn run(self) -> io::Result<(String, u32)> {
+        let (done, finished) = mpsc::channel();
+        let listener = self.listener.try_clone()?;
+        let round = Arc::clone(&self.round);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let round = Arc::clone(&round);
+                let done = done.clone();
+                thread::spawn(move || {
+                    if let Ok(Some(winner)) = handle_client(stream, &round) {
+                        let _ = done.send(winner);
+                    }
+                });
+            }
+        });
+
+        let winner = finished
+            .recv()
+            .map_err(|_| io::Error::other("server stopped before anyone won"))?;
+        let mut round = self.round.lock().unwrap();
+        // 只关读的一端：写线程把排队的 WIN 写完，发送端都丢掉之后自己退出
+        for client in &round.clients {
+            let _ = client.stream.shutdown(Shutdown::Read);
+        }
+        round.clients.clear();
+
+        Ok((winner, round.game.secret()))
+    }
+}
+
+/// 处理一个客户端的所有消息，这个客户端猜中时返回它的名字
+fn handle_client(stream: TcpStream, round: &Mutex<Round>) -> io::Result<Option<String>> {
+    let writer = spawn_writer(stream.try_clone()?)?;
+    let reply = |line: String| {
+        writer
+            .send(line)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    };
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+
+    let name = match lines.next().transpose()? {
+        Some(line) => match line.strip_prefix("NAME ") {
+            Some(name) if !name.trim().is_empty() => name.trim().replace(' ', "_"),
+            _ => {
+                reply(String::from("ERROR expected-name"))?;
+                return Ok(None);
+            }
+        },
+        None => return Ok(None),
+    };
+
+    {
+        let mut round = round.lock().unwrap();
+        if let Some(winner) = &round.winner {
+            reply(format!("WIN {} {}", winner, round.game.secret()))?;
+            return Ok(None);
+        }
+        let config = round.game.config();
+        reply(format!("WELCOME {} {}", config.min, config.max))?;
+        round.clients.push(Client {
+            lines: writer.clone(),
+            stream,
+        });
+    }
+
+    for line in lines {
+        let line = line?;
+        let guess = match line.strip_prefix("GUESS ").map(|n| n.trim().parse::<u32>()) {
+            Some(Ok(guess)) => guess,
+            _ => {
+                reply(String::from("ERROR expected-guess"))?;
+                continue;
+            }
+        };
+
+        let mut round = round.lock().unwrap();
+        let result = match round.game.guess(guess) {
+            Ok(Ordering::Less) => "smaller",
+            Ok(Ordering::Greater) => "bigger",
+            Ok(Ordering::Equal) => "correct",
+            Err(GuessError::OutOfRange) => {
+                let config = round.game.config();
+                reply(format!("ERROR range {} {}", config.min, config.max))?;
+                continue;
+            }
+            Err(GuessError::Finished) => return Ok(None),
+        };
+        round.broadcast(&format!("GUESS {} {} {}", name, guess, result));
+
+        if round.game.state() == State::Won {
+            let secret = round.game.secret();
+            round.winner = Some(name.clone());
+            round.broadcast(&format!("WIN {} {}", name, secret));
+            return Ok(Some(name));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 多人游戏不限次数，只按范围校验
+pub fn round_config(config: &Config) -> Config {
+    Config {
+        max_attempts: u32::MAX,
+        ..config.clone()
+    }
+}
+
+/// 连接服务器：`input` 的每一行作为一次猜测发出，服务器的消息翻译后写到 `output`，
+/// 有人获胜或连接关闭时返回获胜者
+pub fn join(
+    addr: impl ToSocketAddrs,
+    name: &str,
+    lang: Lang,
+    input: impl BufRead + Send + 'static,
+    mut output: impl Write,
+) -> io::Result<Option<String>> {
+    let stream = TcpStream::connect(addr)?;
+    let mut writer = stream.try_clone()?;
+    writeln!(writer, "NAME {}", name)?;
+
+    thread::spawn(move || {
+        for line in input.lines() {
+            let Ok(line) = line else { break };
+            if writeln!(writer, "GUESS {}", line.trim()).is_err() {
+                break;
+            }
+        }
+    });
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let fields: Vec<&str> = line.splitn(4, ' ').collect();
+        match fields.as_slice() {
+            ["WELCOME", min, max] => writeln!(output, "{}", tr!(lang, "join.welcome", min, max))?,
+            ["GUESS", who, guess, result] => {
+                let feedback = match *result {
+                    "smaller" => tr!(lang, "feedback.smaller"),
+                    "bigger" => tr!(lang, "feedback.bigger"),
+                    _ => tr!(lang, "feedback.correct"),
+                };
+                writeln!(output, "{}", tr!(lang, "join.guess", who, guess, feedback))?;
+            }
+            ["WIN", who, secret] => {
+                writeln!(output, "{}", tr!(lang, "join.win", who, secret))?;
+                return Ok(Some(who.to_string()));
+            }
+            ["ERROR", "expected-name"] => writeln!(output, "{}", tr!(lang, "join.expected-name"))?,
+            ["ERROR", "expected-guess"] => {
+                writeln!(output, "{}", tr!(lang, "join.expected-guess"))?
+            }
+            ["ERROR", "range", min, max] => {
+                writeln!(output, "{}", tr!(lang, "play.out-of-range", min, max))?
+            }
+            // 不认识的错误码原样显示
+            ["ERROR", ..] => writeln!(output, "{}", &line["ERROR ".len()..])?,
+            _ => writeln!(output, "{}", line)?,
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(secret: u32) -> (SocketAddr, thread::JoinHandle<io::Result<(String, u32)>>) {
+        let config = Config {
+            player: String::from("host"),
+            ..crate::test_config(1, 100, 10)
+        };
+        let game = Game::with_secret(&round_config(&config), secret);
+        let server = Server::bind("127.0.0.1:0", game).unwrap();
+        let addr = server.local_addr().unwrap();
+        (addr, thread::spawn(move || server.run()))
+    }
+
+    fn connect(addr: SocketAddr, name: &str) -> (TcpStream, io::Lines<BufReader<TcpStream>>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        writeln!(stream, "NAME {}", name).unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        assert_eq!("WELCOME 1 100", lines.next().unwrap().unwrap());
+        (stream, lines)
+    }
+
+    #[test]
+    fn guesses_are_broadcast_and_first_correct_wins() {
+        let (addr, server) = server(42);
+        let (mut alice, mut alice_lines) = connect(addr, "alice");
+        let (mut bob, mut bob_lines) = connect(addr, "bob");
+
+        writeln!(alice, "GUESS 10").unwrap();
+        assert_eq!(
+            "GUESS alice 10 smaller",
+            alice_lines.next().unwrap().unwrap()
+        );
+        assert_eq!("GUESS alice 10 smaller", bob_lines.next().unwrap().unwrap());
+
+        writeln!(bob, "GUESS 500").unwrap();
+        assert_eq!("ERROR range 1 100", bob_lines.next().unwrap().unwrap());
+
+        writeln!(bob, "GUESS 42").unwrap();
+        assert_eq!("GUESS bob 42 correct", alice_lines.next().unwrap().unwrap());
+        assert_eq!("WIN bob 42", alice_lines.next().unwrap().unwrap());
+
+        assert_eq!(("bob".to_string(), 42), server.join().unwrap().unwrap());
+    }
+
+    #[test]
+    fn join_plays_scripted_guesses() {
+        let (addr, server) = server(7);
+        let mut output = Vec::new();
+
+        let winner = join(addr, "carol", Lang::En, &b"50\n5\n7\n"[..], &mut output).unwrap();
+
+        assert_eq!(Some("carol".to_string()), winner);
+        assert_eq!(
+            "Joined! Guess a number between 1 and 100\n\
+             carol guessed 50: is bigger\n\
+             carol guessed 5: is smaller\n\
+             carol guessed 7: is correct\n\
+             ~~carol wins~~ The secret number was 7\n",
+            String::from_utf8(output).unwrap()
+        );
+        assert_eq!("carol", server.join().unwrap().unwrap().0);
+    }
+
+    #[test]
+    fn join_translates_errors() {
+        let (addr, server) = server(7);
+        let mut output = Vec::new();
+
+        let input = &b"abc\n500\n7\n"[..];
+        let winner = join(addr, "dave", Lang::Zh, input, &mut output).unwrap();
+
+        assert_eq!(Some("dave".to_string()), winner);
+        assert_eq!(
+            "已加入！猜一个 1 到 100 之间的数字\n\
+             请输入一个数字\n\
+             数字必须在 1 到 100 之间\n\
+             dave 猜了 7：猜对了\n\
+             ~~dave 赢了~~ 秘密数字是 7\n",
+            String::from_utf8(output).unwrap()
+        );
+        assert_eq!("dave", server.join().unwrap().unwrap().0);
+    }
+
+    #[test]
+    fn errors_and_broadcasts_share_one_writer() {
+        let (addr, server) = server(42);
+        let (mut alice, mut alice_lines) = connect(addr, "alice");
+        let (gone, _) = connect(addr, "gone");
+        drop(gone);
+
+        // 错误和广播交替发，每一行都应该完整、按顺序到达
+        for _ in 0..50 {
+            writeln!(alice, "GUESS 500\nGUESS 10").unwrap();
+        }
+        for _ in 0..50 {
+            assert_eq!("ERROR range 1 100", alice_lines.next().unwrap().unwrap());
+            assert_eq!(
+                "GUESS alice 10 smaller",
+                alice_lines.next().unwrap().unwrap()
+            );
+        }
+
+        writeln!(alice, "GUESS 42").unwrap();
+        assert_eq!(
+            "GUESS alice 42 correct",
+            alice_lines.next().unwrap().unwrap()
+        );
+        assert_eq!("WIN alice 42", alice_lines.next().unwrap().unwrap());
+        assert!(alice_lines.next().is_none());
+        assert_eq!(("alice".to_string(), 42), server.join().unwrap().unwrap());
+    }
+}
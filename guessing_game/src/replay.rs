@@ -0,0 +1,309 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::Lang;
+use crate::{tr, Attempt, Config, Difficulty, Game, State, Variant};
+
+const HEADER: &str = "guessing_game replay 1";
+
+/// 一局游戏的完整记录：种子、设置、每次猜测及结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub seed: u64,
+    pub config: Config,
+    pub secret: u32,
+    pub attempts: Vec<Attempt>,
+    pub state: State,
+}
+
+impl Session {
+    pub fn new(seed: u64, game: &Game) -> Session {
+        Session {
+            seed,
+            config: game.config().clone(),
+            secret: game.secret(),
+            attempts: game.history().to_vec(),
+            state: game.state(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut out = Vec::new();
+        writeln!(out, "{}", HEADER)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "range {} {}", self.config.min, self.config.max)?;
+        writeln!(out, "max_attempts {}", self.config.max_attempts)?;
+        if let Some(difficulty) = self.config.difficulty {
+            writeln!(out, "difficulty {}", difficulty.name())?;
+        }
+        match self.config.variant {
+            Variant::Number => {}
+            Variant::Code(length) => writeln!(out, "variant code {}", length)?,
+            Variant::Word => writeln!(out, "variant word")?,
+        }
+        writeln!(out, "player {}", self.config.player)?;
+        for attempt in &self.attempts {
+            writeln!(out, "guess {} {}", attempt.at, attempt.guess)?;
+        }
+        writeln!(out, "secret {}", self.secret)?;
+        writeln!(out, "outcome {}", state_name(self.state))?;
+
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Session, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(format!("{} is not a replay file", path.display()).into());
+        }
+
+        let mut seed = None;
+        let mut range = None;
+        let mut max_attempts = None;
+        let mut secret = None;
+        let mut state = None;
+        let mut difficulty = None;
+        // 没有 variant 行的是猜数字
+        let mut variant = Variant::Number;
+        let mut player = String::new();
+        let mut attempts = Vec::new();
+        for line in lines {
+            // 玩家名可能含空格，单独处理
+            if let Some(name) = line.strip_prefix("player ") {
+                player = name.to_string();
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["seed", value] => seed = Some(value.parse()?),
+                ["range", min, max] => range = Some((min.parse()?, max.parse()?)),
+                ["max_attempts", value] => max_attempts = Some(value.parse()?),
+                ["difficulty", name] => {
+                    difficulty = Some(Difficulty::from_name(name).ok_or("unknown difficulty")?)
+                }
+                ["variant", "code", length] => variant = Variant::Code(length.parse()?),
+                ["variant", "word"] => variant = Variant::Word,
+                ["guess", at, guess] => attempts.push(Attempt {
+                    guess: guess.parse()?,
+                    at: at.parse()?,
+                }),
+                ["secret", value] => secret = Some(value.parse()?),
+                ["outcome", name] => state = Some(state_from_name(name).ok_or("unknown outcome")?),
+                [] => {}
+                _ => return Err(format!("malformed replay line: {}", line).into()),
+            }
+        }
+
+        let (min, max) = range.ok_or("replay has no range")?;
+        Ok(Session {
+            seed: seed.ok_or("replay has no seed")?,
+            config: Config {
+                min,
+                max,
+                max_attempts: max_attempts.ok_or("replay has no max_attempts")?,
+                seed,
+                record: None,
+                difficulty,
+                player,
+                variant,
+                lang: Lang::En,
+            },
+            secret: secret.ok_or("replay has no secret")?,
+            attempts,
+            state: state.ok_or("replay has no outcome")?,
+        })
+    }
+
+    /// 默认保存到 数据目录/replays/<开始时间>-<种子>.replay
+    pub fn default_path(&self) -> PathBuf {
+        let started = self
+            .attempts
+            .first()
+            .map(|attempt| attempt.at)
+            .unwrap_or_else(crate::now_millis);
+        crate::data_dir()
+            .join("replays")
+            .join(format!("{}-{}.replay", started, self.seed))
+    }
+
+    /// 用记录的种子和猜测重新跑一遍，结果和记录不一致时返回错误
+    pub fn replay(&self, lang: Lang, mut output: impl Write) -> Result<Game, Box<dyn Error>> {
+        let mut game = Game::new(&self.config, &mut StdRng::seed_from_u64(self.seed));
+        if game.secret() != self.secret {
+            return Err(tr!(lang, "replay.secret-mismatch", self.secret, game.secret()).into());
+        }
+
+        let variant = self.config.variant;
+        for attempt in &self.attempts {
+            let feedback = match game.guess(attempt.guess) {
+                Ok(Ordering::Equal) => tr!(lang, "play.win"),
+                Ok(_) => variant.feedback(attempt.guess, game.secret(), lang),
+                Err(err) => {
+                    let err = format!("{:?}", err);
+                    return Err(tr!(lang, "replay.rejected", attempt.guess, err).into());
+                }
+            };
+            writeln!(output, "{} -> {}", variant.display(attempt.guess), feedback)?;
+        }
+
+        if game.state() != self.state {
+            return Err(tr!(
+                lang,
+                "replay.outcome-mismatch",
+                outcome(lang, self.state),
+                outcome(lang, game.state())
+            )
+            .into());
+        }
+        writeln!(
+            output,
+            "{}",
+            tr!(
+                lang,
+                "replay.matches",
+                outcome(lang, game.state()),
+                game.attempts(),
+                variant.display(game.secret())
+            )
+        )?;
+
+        Ok(game)
+    }
+}
+
+fn state_name(state: State) -> &'static str {
+    match state {
+        State::Playing => "abandoned",
+        State::Won => "won",
+        State::Lost => "lost",
+    }
+}
+
+/// 给玩家看的结局，文件里仍然用 `state_name`
+fn outcome(lang: Lang, state: State) -> String {
+    match state {
+        State::Playing => tr!(lang, "state.abandoned"),
+        State::Won => tr!(lang, "state.won"),
+        State::Lost => tr!(lang, "state.lost"),
+    }
+}
+
+fn state_from_name(name: &str) -> Option<State> {
+    match name {
+        "abandoned" => Some(State::Playing),
+        "won" => Some(State::Won),
+        "lost" => Some(State::Lost),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        let config = Config {
+            seed: Some(9),
+            difficulty: Some(Difficulty::Normal),
+            player: String::from("Ferris the crab"),
+            ..crate::test_config(1, 100, 3)
+        };
+        let mut game = Game::new(&config, &mut StdRng::seed_from_u64(9));
+        let wrong = if game.secret() == 1 { 2 } else { 1 };
+        game.guess(wrong).unwrap();
+        game.guess(game.secret()).unwrap();
+        Session::new(9, &game)
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("guessing_game-{}.replay", std::process::id()));
+        let session = session();
+
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(session, loaded);
+    }
+
+    #[test]
+    fn word_session_round_trip() {
+        let config = Variant::Word.apply(Config {
+            seed: Some(4),
+            ..session().config
+        });
+        let mut game = Game::new(&config, &mut StdRng::seed_from_u64(4));
+        let crane = Variant::Word.parse("crane", Lang::En).unwrap();
+        if game.secret() != crane {
+            game.guess(crane).unwrap();
+        }
+        game.guess(game.secret()).unwrap();
+        let session = Session::new(4, &game);
+
+        let path =
+            std::env::temp_dir().join(format!("guessing_game-word-{}.replay", std::process::id()));
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(session, loaded);
+
+        let mut output = Vec::new();
+        loaded.replay(Lang::En, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let secret = Variant::Word.display(game.secret());
+        assert!(output.ends_with(&format!(
+            "{} -> ~~win win win~~\nReplay matches: won after {} attempts, secret {}\n",
+            secret,
+            game.attempts(),
+            secret
+        )));
+    }
+
+    #[test]
+    fn replay_verifies_outcome() {
+        let session = session();
+        let mut output = Vec::new();
+
+        let game = session.replay(Lang::En, &mut output).unwrap();
+        assert_eq!(State::Won, game.state());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(&format!(
+            "Replay matches: won after 2 attempts, secret {}\n",
+            session.secret
+        )));
+
+        let mut output = Vec::new();
+        session.replay(Lang::Zh, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("-> 小了\n"));
+        assert!(output.ends_with(&format!(
+            "回放一致：猜了 2 次后赢了，秘密是 {}\n",
+            session.secret
+        )));
+    }
+
+    #[test]
+    fn tampered_replay_is_detected() {
+        let mut session = session();
+        session.state = State::Lost;
+
+        assert!(session.replay(Lang::En, Vec::new()).is_err());
+
+        let mut session = self::session();
+        session.secret += 1;
+        assert!(session.replay(Lang::En, Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use rand::Rng;
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+use crate::solver::{Contradiction, Solver, Strategy};
+use crate::{tr, Config};
+
+/// 反过来玩的结果：玩家心里想一个数，程序来猜
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Guessed { number: u32, attempts: u32 },
+    Contradiction(Contradiction),
+    GaveUp,
+    Abandoned,
+}
+
+/// 玩家的回答换算成“猜测相对于秘密数字”的大小，和 `Game::guess` 一致
+fn parse_answer(answer: &str) -> Option<Ordering> {
+    match answer.trim().to_lowercase().as_str() {
+        "h" | "higher" => Some(Ordering::Less),
+        "l" | "lower" => Some(Ordering::Greater),
+        "c" | "correct" | "y" | "yes" => Some(Ordering::Equal),
+        "大" => Some(Ordering::Less),
+        "小" => Some(Ordering::Greater),
+        "对" | "是" => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// 程序按 `strategy` 猜、从 `input` 读玩家的 higher / lower / correct，回答前后矛盾时结束
+pub fn reverse(
+    config: &Config,
+    strategy: Strategy,
+    rng: &mut impl Rng,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<Verdict> {
+    let lang = config.lang;
+    let mut solver = Solver::new(strategy, config.min, config.max);
+
+    writeln!(output, "{}", tr!(lang, "reverse.title"))?;
+    writeln!(
+        output,
+        "{}",
+        tr!(
+            lang,
+            "reverse.intro",
+            config.min,
+            config.max,
+            config.max_attempts
+        )
+    )?;
+
+    for attempt in 1..=config.max_attempts {
+        let guess = solver.next_guess(rng);
+
+        let ordering = loop {
+            writeln!(output, "{}", tr!(lang, "reverse.ask", guess))?;
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                return Ok(Verdict::Abandoned);
+            }
+            match parse_answer(&answer) {
+                Some(ordering) => break ordering,
+                None => writeln!(output, "{}", tr!(lang, "reverse.answer-usage"))?,
+            }
+        };
+
+        if let Err(contradiction) = solver.feedback(guess, ordering) {
+            let message = match contradiction.earlier {
+                Some((earlier, Ordering::Less)) => tr!(lang, "reverse.lie-higher", earlier),
+                Some((earlier, _)) => tr!(lang, "reverse.lie-lower", earlier),
+                None => tr!(lang, "reverse.lie-range", config.min, config.max),
+            };
+            writeln!(output, "{}", message)?;
+            return Ok(Verdict::Contradiction(contradiction));
+        }
+
+        if ordering == Ordering::Equal {
+            writeln!(output, "{}", tr!(lang, "reverse.win", guess, attempt))?;
+            return Ok(Verdict::Guessed {
+                number: guess,
+                attempts: attempt,
+            });
+        }
+    }
+
+    let (low, high) = solver.range();
+    writeln!(output, "{}", tr!(lang, "reverse.give-up", low, high))?;
+    Ok(Verdict::GaveUp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn run(max_attempts: u32, script: &str) -> (Verdict, String) {
+        run_in(crate::Lang::En, max_attempts, script)
+    }
+
+    fn run_in(lang: crate::Lang, max_attempts: u32, script: &str) -> (Verdict, String) {
+        let config = Config {
+            lang,
+            ..crate::test_config(1, 100, max_attempts)
+        };
+        let mut output = Vec::new();
+        let verdict = reverse(
+            &config,
+            Strategy::Binary,
+            &mut StdRng::seed_from_u64(0),
+            script.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        (verdict, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn honest_player() {
+        // 想的是 30：50 -> 25 -> 37 -> 31 -> 28 -> 29 -> 30
+        let (verdict, output) = run(10, "lower\nh\nl\nl\nhigher\nh\nyes\n");
+
+        assert_eq!(
+            Verdict::Guessed {
+                number: 30,
+                attempts: 7
+            },
+            verdict
+        );
+        assert!(output.contains("Is it 37? (higher/lower/correct)"));
+        assert!(output.contains("~~I win~~ Your number is 30, found in 7 attempts"));
+    }
+
+    #[test]
+    fn lying_player_is_caught() {
+        // 50 -> 25 -> 37 -> 31 -> 34 -> 35 -> 36：说比 35 大又比 36 小
+        let (verdict, output) = run(10, "l\nh\nl\nh\nh\nh\nl\n");
+
+        assert_eq!(
+            Verdict::Contradiction(Contradiction {
+                earlier: Some((35, Ordering::Less))
+            }),
+            verdict
+        );
+        assert!(output.ends_with(
+            "That contradicts your earlier answer: you said the number is higher than 35\n"
+        ));
+    }
+
+    #[test]
+    fn answers_outside_the_range() {
+        let (verdict, output) = run(10, "maybe\nh\nh\nh\nh\nh\nh\nh\n");
+
+        assert_eq!(
+            Verdict::Contradiction(Contradiction { earlier: None }),
+            verdict
+        );
+        assert!(output.contains("Please answer higher, lower or correct"));
+        assert!(output.contains("the number must be between 1 and 100"));
+    }
+
+    #[test]
+    fn gives_up_and_stops_at_eof() {
+        let (verdict, output) = run(2, "h\nh\n");
+        assert_eq!(Verdict::GaveUp, verdict);
+        assert!(output.ends_with("~~I give up~~ Your number is somewhere between 76 and 100\n"));
+
+        assert_eq!(Verdict::Abandoned, run(10, "h\n").0);
+    }
+
+    #[test]
+    fn chinese_answers() {
+        let (verdict, output) = run_in(crate::Lang::Zh, 10, "小\n大\n对\n");
+
+        assert_eq!(
+            Verdict::Guessed {
+                number: 37,
+                attempts: 3
+            },
+            verdict
+        );
+        assert!(output.contains("是 37 吗？"));
+        assert!(output.ends_with("~~我赢了~~ 你想的是 37，我猜了 3 次\n"));
+    }
+}
@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 一条获胜记录，`difficulty` 为 `None` 表示自定义设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub player: String,
+    pub difficulty: Option<String>,
+    pub attempts: u32,
+    pub millis: u64,
+    pub points: u32,
+    pub at: u64,
+}
+
+/// 分数保存在一个按行追加的文本文件里，读写时加文件锁，多个进程同时写也不会交错
+pub struct ScoreStore {
+    path: PathBuf,
+}
+
+impl ScoreStore {
+    pub fn new(path: &Path) -> ScoreStore {
+        ScoreStore {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn open_default() -> ScoreStore {
+        ScoreStore::new(&crate::data_dir().join("scores.tsv"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add(&self, score: &Score) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let player: String = score
+            .player
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        let line = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            score.at,
+            score.difficulty.as_deref().unwrap_or("custom"),
+            score.attempts,
+            score.millis,
+            score.points,
+            player
+        );
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.lock()?;
+        file.write_all(line.as_bytes())?;
+        file.unlock()?;
+
+        Ok(())
+    }
+
+    pub fn load(&self) -> Result<Vec<Score>, Box<dyn Error>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut contents = String::new();
+        file.lock_shared()?;
+        file.read_to_string(&mut contents)?;
+        file.unlock()?;
+
+        let mut scores = Vec::new();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            // 没有得分那一列的旧记录按没用提示算分
+            let fields: Vec<&str> = line.splitn(6, '\t').collect();
+            let (at, difficulty, attempts, millis, points, player) = match fields[..] {
+                [at, difficulty, attempts, millis, points, player] => {
+                    (at, difficulty, attempts, millis, Some(points), player)
+                }
+                [at, difficulty, attempts, millis, player] => {
+                    (at, difficulty, attempts, millis, None, player)
+                }
+                _ => return Err(format!("malformed score line: {}", line).into()),
+            };
+            let attempts = attempts.parse()?;
+            let millis = millis.parse()?;
+            let points = match points {
+                Some(points) => points.parse()?,
+                None => crate::hints::points(attempts, 0, millis),
+            };
+            scores.push(Score {
+                player: player.to_string(),
+                difficulty: (difficulty != "custom").then(|| difficulty.to_string()),
+                attempts,
+                millis,
+                points,
+                at: at.parse()?,
+            });
+        }
+
+        Ok(scores)
+    }
+}
+
+/// 某个难度的排行榜：得分高的在前，得分相同时次数少、用时短的在前
+pub fn leaderboard<'a>(scores: &'a [Score], difficulty: Option<&str>) -> Vec<&'a Score> {
+    let mut board: Vec<&Score> = scores
+        .iter()
+        .filter(|score| score.difficulty.as_deref() == difficulty)
+        .collect();
+    board.sort_by_key(|score| {
+        (
+            std::cmp::Reverse(score.points),
+            score.attempts,
+            score.millis,
+            score.at,
+        )
+    });
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn store(name: &str) -> ScoreStore {
+        let path =
+            std::env::temp_dir().join(format!("guessing_game-{}-{}.tsv", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        ScoreStore::new(&path)
+    }
+
+    fn score(player: &str, difficulty: Option<&str>, attempts: u32, millis: u64) -> Score {
+        Score {
+            player: player.to_string(),
+            difficulty: difficulty.map(String::from),
+            attempts,
+            millis,
+            points: crate::hints::points(attempts, 0, millis),
+            at: 1,
+        }
+    }
+
+    #[test]
+    fn add_and_rank() {
+        let store = store("rank");
+        store.add(&score("slow", Some("easy"), 3, 9000)).unwrap();
+        store.add(&score("fast", Some("easy"), 3, 1000)).unwrap();
+        store.add(&score("lucky", Some("hard"), 1, 500)).unwrap();
+        store.add(&score("tab\tname", None, 2, 10)).unwrap();
+
+        let scores = store.load().unwrap();
+        fs::remove_file(store.path()).unwrap();
+
+        let easy: Vec<&str> = leaderboard(&scores, Some("easy"))
+            .iter()
+            .map(|score| score.player.as_str())
+            .collect();
+        assert_eq!(vec!["fast", "slow"], easy);
+        assert_eq!("tab name", leaderboard(&scores, None)[0].player);
+    }
+
+    #[test]
+    fn hints_lower_the_rank_and_old_lines_still_load() {
+        let store = store("points");
+        let mut hinted = score("hinted", Some("normal"), 2, 1000);
+        hinted.points = crate::hints::points(2, 150, 1000);
+        store.add(&hinted).unwrap();
+        store.add(&score("plain", Some("normal"), 3, 1000)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(store.path()).unwrap();
+        writeln!(file, "7\tnormal\t1\t2000\tveteran").unwrap();
+
+        let scores = store.load().unwrap();
+        fs::remove_file(store.path()).unwrap();
+
+        let normal: Vec<(&str, u32)> = leaderboard(&scores, Some("normal"))
+            .iter()
+            .map(|score| (score.player.as_str(), score.points))
+            .collect();
+        assert_eq!(
+            vec![("veteran", 998), ("plain", 899), ("hinted", 799)],
+            normal
+        );
+    }
+
+    #[test]
+    fn concurrent_writes_do_not_interleave() {
+        let path = store("concurrent").path().to_path_buf();
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let store = ScoreStore::new(&path);
+                    for j in 0..25 {
+                        store
+                            .add(&score(&format!("player {}", i), Some("normal"), j, 0))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let scores = ScoreStore::new(&path).load().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(200, scores.len());
+    }
+}
@@ -0,0 +1,293 @@
+use rand::Rng;
+use std::cmp::Ordering;
+use std::io::{self, Write};
+
+use crate::{tr, Config, Game, State, Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Binary,
+    Random,
+    /// 故意很笨：从最小值开始一个一个往上猜
+    Linear,
+}
+
+impl Strategy {
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name {
+            "binary" => Some(Strategy::Binary),
+            "random" => Some(Strategy::Random),
+            "linear" => Some(Strategy::Linear),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Binary => "binary",
+            Strategy::Random => "random",
+            Strategy::Linear => "linear",
+        }
+    }
+}
+
+/// 回答和之前的回答矛盾，`earlier` 是与之冲突的那次猜测及其回答，`None` 表示冲突的是范围本身
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub earlier: Option<(u32, Ordering)>,
+}
+
+/// 记住秘密数字可能所在的区间，按策略在区间里选下一个猜测
+pub struct Solver {
+    strategy: Strategy,
+    low: u32,
+    high: u32,
+    // 把 low / high 收窄到现在这样的那次猜测
+    low_set_by: Option<u32>,
+    high_set_by: Option<u32>,
+}
+
+impl Solver {
+    pub fn new(strategy: Strategy, min: u32, max: u32) -> Solver {
+        Solver {
+            strategy,
+            low: min,
+            high: max,
+            low_set_by: None,
+            high_set_by: None,
+        }
+    }
+
+    pub fn next_guess(&self, rng: &mut impl Rng) -> u32 {
+        match self.strategy {
+            Strategy::Binary => self.low + (self.high - self.low) / 2,
+            Strategy::Random => rng.gen_range(self.low..=self.high),
+            Strategy::Linear => self.low,
+        }
+    }
+
+    /// `ordering` 是猜测相对于秘密数字的大小，和 `Game::guess` 的返回值一致；
+    /// 回答让剩下的区间变空时返回 `Contradiction`，区间保持不变
+    pub fn feedback(&mut self, guess: u32, ordering: Ordering) -> Result<(), Contradiction> {
+        let too_low = Contradiction {
+            earlier: self.low_set_by.map(|g| (g, Ordering::Less)),
+        };
+        let too_high = Contradiction {
+            earlier: self.high_set_by.map(|g| (g, Ordering::Greater)),
+        };
+
+        match ordering {
+            Ordering::Less if guess >= self.high => return Err(too_high),
+            Ordering::Less if guess < self.low => {}
+            Ordering::Less => {
+                self.low = guess + 1;
+                self.low_set_by = Some(guess);
+            }
+            Ordering::Greater if guess <= self.low => return Err(too_low),
+            Ordering::Greater if guess > self.high => {}
+            Ordering::Greater => {
+                self.high = guess - 1;
+                self.high_set_by = Some(guess);
+            }
+            Ordering::Equal if guess < self.low => return Err(too_low),
+            Ordering::Equal if guess > self.high => return Err(too_high),
+            Ordering::Equal => {
+                self.low = guess;
+                self.high = guess;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 秘密数字还可能在的区间
+    pub fn range(&self) -> (u32, u32) {
+        (self.low, self.high)
+    }
+}
+
+/// 用 `Game` 当裁判让 `strategy` 玩一局
+pub fn solve(config: &Config, strategy: Strategy, rng: &mut impl Rng) -> Game {
+    let mut game = Game::new(config, rng);
+    let mut solver = Solver::new(strategy, config.min, config.max);
+
+    while game.state() == State::Playing {
+        let guess = solver.next_guess(rng);
+        let ordering = game
+            .guess(guess)
+            .expect("the solver only guesses inside the range");
+        solver
+            .feedback(guess, ordering)
+            .expect("the game always answers consistently");
+    }
+
+    game
+}
+
+/// 多局自动游戏的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub games: u32,
+    pub wins: u32,
+    pub min_attempts: u32,
+    pub max_attempts: u32,
+    pub mean_attempts: f64,
+}
+
+impl Stats {
+    pub fn from_games(games: &[Game]) -> Stats {
+        let attempts: Vec<u32> = games.iter().map(Game::attempts).collect();
+        let total: u64 = attempts.iter().map(|&n| n as u64).sum();
+
+        Stats {
+            games: games.len() as u32,
+            wins: games.iter().filter(|g| g.state() == State::Won).count() as u32,
+            min_attempts: attempts.iter().copied().min().unwrap_or(0),
+            max_attempts: attempts.iter().copied().max().unwrap_or(0),
+            mean_attempts: if games.is_empty() {
+                0.0
+            } else {
+                total as f64 / games.len() as f64
+            },
+        }
+    }
+}
+
+/// 自动玩 `games` 局：只有一局时打印每一步，多局时打印统计
+pub fn autoplay(
+    config: &Config,
+    strategy: Strategy,
+    games: u32,
+    rng: &mut impl Rng,
+    mut output: impl Write,
+) -> io::Result<Stats> {
+    let played: Vec<Game> = (0..games).map(|_| solve(config, strategy, rng)).collect();
+
+    if let [game] = played.as_slice() {
+        for attempt in game.history() {
+            let feedback = Variant::Number.feedback(attempt.guess, game.secret(), config.lang);
+            writeln!(
+                output,
+                "{}",
+                tr!(config.lang, "autoplay.guess", attempt.guess, feedback)
+            )?;
+        }
+    }
+
+    let stats = Stats::from_games(&played);
+    writeln!(
+        output,
+        "{}",
+        tr!(
+            config.lang,
+            "autoplay.stats",
+            strategy.name(),
+            stats.wins,
+            stats.games,
+            stats.min_attempts,
+            format!("{:.2}", stats.mean_attempts),
+            stats.max_attempts
+        )
+    )?;
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn config(max: u32, max_attempts: u32) -> Config {
+        crate::test_config(1, max, max_attempts)
+    }
+
+    #[test]
+    fn contradictions_name_the_earlier_answer() {
+        let mut solver = Solver::new(Strategy::Binary, 1, 10);
+
+        assert_eq!(Ok(()), solver.feedback(5, Ordering::Less));
+        assert_eq!(Ok(()), solver.feedback(8, Ordering::Greater));
+        assert_eq!((6, 7), solver.range());
+        assert_eq!(
+            Err(Contradiction {
+                earlier: Some((8, Ordering::Greater))
+            }),
+            solver.feedback(7, Ordering::Less)
+        );
+        assert_eq!(
+            Err(Contradiction {
+                earlier: Some((5, Ordering::Less))
+            }),
+            solver.feedback(6, Ordering::Greater)
+        );
+        assert_eq!((6, 7), solver.range());
+
+        let mut solver = Solver::new(Strategy::Binary, 1, 10);
+        assert_eq!(
+            Err(Contradiction { earlier: None }),
+            solver.feedback(10, Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn binary_search_always_wins_within_log2() {
+        let config = config(1000, 10);
+        let mut rng = StdRng::seed_from_u64(1);
+        let games: Vec<Game> = (0..200)
+            .map(|_| solve(&config, Strategy::Binary, &mut rng))
+            .collect();
+        let stats = Stats::from_games(&games);
+
+        assert_eq!(200, stats.wins);
+        assert!(stats.max_attempts <= 10);
+    }
+
+    #[test]
+    fn linear_guesses_in_order() {
+        let config = config(100, 100);
+        let mut game = solve(&config, Strategy::Linear, &mut StdRng::seed_from_u64(3));
+        let secret = game.secret();
+
+        assert_eq!(State::Won, game.state());
+        assert_eq!(secret, game.attempts());
+        assert_eq!(Err(crate::GuessError::Finished), game.guess(1));
+    }
+
+    #[test]
+    fn strategies_are_ranked() {
+        let config = config(100, 100);
+        let mean = |strategy| {
+            let mut output = Vec::new();
+            autoplay(
+                &config,
+                strategy,
+                300,
+                &mut StdRng::seed_from_u64(7),
+                &mut output,
+            )
+            .unwrap()
+            .mean_attempts
+        };
+
+        let binary = mean(Strategy::Binary);
+        let random = mean(Strategy::Random);
+        let linear = mean(Strategy::Linear);
+        assert!(binary < random, "{} < {}", binary, random);
+        assert!(random < linear, "{} < {}", random, linear);
+    }
+
+    #[test]
+    fn single_game_prints_every_guess() {
+        let mut output = Vec::new();
+        let mut rng = StdRng::seed_from_u64(5);
+        let secret = Game::new(&config(10, 10), &mut StdRng::seed_from_u64(5)).secret();
+
+        autoplay(&config(10, 10), Strategy::Linear, 1, &mut rng, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(secret as usize + 1, output.lines().count());
+        assert!(output.contains(&format!("Guess {}: is correct", secret)));
+    }
+}
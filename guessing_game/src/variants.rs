@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+
+use crate::i18n::Lang;
+use crate::{tr, Config};
+
+/// 内置的五字母单词表，一行一个
+const WORD_LIST: &str = include_str!("words.txt");
+
+pub fn words() -> impl Iterator<Item = &'static str> {
+    WORD_LIST
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+}
+
+/// 游戏玩法。所有玩法的秘密和猜测都是 `min..=max` 里的数，这样 `Game`、回放和计分可以共用：
+/// 密码是 `length` 位的数字串，单词是单词表里的下标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Number,
+    /// Mastermind / 猜数字（Bulls and Cows），数字可以重复
+    Code(u32),
+    /// Wordle 式猜单词
+    Word,
+}
+
+impl Variant {
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Number => "number",
+            Variant::Code(_) => "code",
+            Variant::Word => "word",
+        }
+    }
+
+    /// 玩法自己的默认次数，数字游戏的次数由难度决定
+    pub fn default_attempts(self) -> Option<u32> {
+        match self {
+            Variant::Number => None,
+            Variant::Code(_) => Some(10),
+            Variant::Word => Some(6),
+        }
+    }
+
+    /// 按玩法改写范围和次数；没有自定义时用玩法自己的默认次数
+    pub fn apply(self, config: Config) -> Config {
+        let max = match self {
+            Variant::Number => {
+                return Config {
+                    variant: self,
+                    ..config
+                }
+            }
+            Variant::Code(length) => 10u32.pow(length) - 1,
+            Variant::Word => words().count() as u32 - 1,
+        };
+
+        Config {
+            min: 0,
+            max,
+            max_attempts: match self.default_attempts() {
+                Some(attempts) if config.difficulty.is_some() => attempts,
+                _ => config.max_attempts,
+            },
+            difficulty: None,
+            variant: self,
+            ..config
+        }
+    }
+
+    /// 提示语里对秘密的称呼
+    pub fn noun(self, lang: Lang) -> String {
+        match self {
+            Variant::Number => tr!(lang, "noun.number"),
+            Variant::Code(_) => tr!(lang, "noun.code"),
+            Variant::Word => tr!(lang, "noun.word"),
+        }
+    }
+
+    /// 开局时的说明
+    pub fn intro(self, config: &Config) -> String {
+        let lang = config.lang;
+        match self {
+            Variant::Number => tr!(
+                lang,
+                "intro.number",
+                config.min,
+                config.max,
+                config.max_attempts
+            ),
+            Variant::Code(length) => tr!(lang, "intro.code", length, config.max_attempts),
+            Variant::Word => tr!(lang, "intro.word", config.max_attempts),
+        }
+    }
+
+    /// 把玩家的输入变成一次猜测，失败时返回给玩家看的原因
+    pub fn parse(self, input: &str, lang: Lang) -> Result<u32, String> {
+        match self {
+            Variant::Number => input.parse().map_err(|_| tr!(lang, "parse.number", input)),
+            Variant::Code(length) => {
+                if input.len() == length as usize && input.bytes().all(|b| b.is_ascii_digit()) {
+                    Ok(input.parse().expect("checked to be digits"))
+                } else {
+                    Err(tr!(lang, "parse.code", input, length))
+                }
+            }
+            Variant::Word => {
+                let input = input.to_lowercase();
+                words()
+                    .position(|word| word == input)
+                    .map(|index| index as u32)
+                    .ok_or_else(|| tr!(lang, "parse.word", input))
+            }
+        }
+    }
+
+    /// 把猜测或秘密按玩法显示出来
+    pub fn display(self, value: u32) -> String {
+        match self {
+            Variant::Number => value.to_string(),
+            Variant::Code(length) => format!("{:0width$}", value, width = length as usize),
+            Variant::Word => words().nth(value as usize).unwrap_or("?????").to_string(),
+        }
+    }
+
+    /// 一次猜测的反馈
+    pub fn feedback(self, guess: u32, secret: u32, lang: Lang) -> String {
+        match self {
+            Variant::Number => match guess.cmp(&secret) {
+                Ordering::Less => tr!(lang, "feedback.smaller"),
+                Ordering::Greater => tr!(lang, "feedback.bigger"),
+                Ordering::Equal => tr!(lang, "feedback.correct"),
+            },
+            Variant::Code(_) => {
+                let (bulls, cows) = bulls_and_cows(&self.display(guess), &self.display(secret));
+                tr!(lang, "feedback.bulls-cows", bulls, cows)
+            }
+            Variant::Word => letter_marks(&self.display(guess), &self.display(secret)),
+        }
+    }
+}
+
+/// 位置和数字都对的是 bull，数字对但位置不对的是 cow
+pub fn bulls_and_cows(guess: &str, secret: &str) -> (u32, u32) {
+    let bulls = guess
+        .bytes()
+        .zip(secret.bytes())
+        .filter(|(g, s)| g == s)
+        .count() as u32;
+    let common: u32 = (b'0'..=b'9')
+        .map(|digit| {
+            let count = |code: &str| code.bytes().filter(|&b| b == digit).count() as u32;
+            count(guess).min(count(secret))
+        })
+        .sum();
+
+    (bulls, common - bulls)
+}
+
+/// Wordle 规则：`+` 字母和位置都对，`?` 字母在别的位置，`-` 没有这个字母。
+/// 重复字母按秘密单词里的个数分配，先分给位置正确的
+pub fn letter_marks(guess: &str, secret: &str) -> String {
+    let guess: Vec<char> = guess.chars().collect();
+    let secret: Vec<char> = secret.chars().collect();
+    let mut marks = vec!['-'; guess.len()];
+    let mut unmatched: Vec<char> = Vec::new();
+
+    for (i, &c) in guess.iter().enumerate() {
+        if secret.get(i) == Some(&c) {
+            marks[i] = '+';
+        } else if let Some(&s) = secret.get(i) {
+            unmatched.push(s);
+        }
+    }
+    for (i, c) in guess.iter().enumerate() {
+        if marks[i] == '+' {
+            continue;
+        }
+        if let Some(pos) = unmatched.iter().position(|s| s == c) {
+            unmatched.swap_remove(pos);
+            marks[i] = '?';
+        }
+    }
+
+    marks.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_list_is_clean() {
+        let list: Vec<&str> = words().collect();
+        assert!(list.len() > 100);
+        for word in &list {
+            assert_eq!(5, word.len(), "{}", word);
+            assert!(word.bytes().all(|b| b.is_ascii_lowercase()), "{}", word);
+        }
+        let mut sorted = list.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(list.len(), sorted.len());
+    }
+
+    #[test]
+    fn code_feedback() {
+        assert_eq!((4, 0), bulls_and_cows("1234", "1234"));
+        assert_eq!((0, 4), bulls_and_cows("4321", "1234"));
+        assert_eq!((1, 2), bulls_and_cows("1122", "1213"));
+        assert_eq!((0, 0), bulls_and_cows("5555", "1234"));
+
+        let code = Variant::Code(4);
+        assert_eq!(Ok(42), code.parse("0042", Lang::En));
+        assert!(code.parse("42", Lang::En).is_err());
+        assert_eq!(
+            Err(String::from("“12a4”不是 4 位密码")),
+            code.parse("12a4", Lang::Zh)
+        );
+        assert_eq!("0042", code.display(42));
+        assert_eq!("1 bulls, 2 cows", code.feedback(1122, 1213, Lang::En));
+    }
+
+    #[test]
+    fn word_feedback() {
+        assert_eq!("+++++", letter_marks("crane", "crane"));
+        assert_eq!("-++?+", letter_marks("trace", "crane"));
+        // 秘密里只有一个 e，位置正确的那个先拿走
+        assert_eq!("---++", letter_marks("geese", "those"));
+        assert_eq!("?+--?", letter_marks("level", "hello"));
+
+        let word = Variant::Word;
+        let crane = word.parse("CRANE", Lang::En).unwrap();
+        assert_eq!("crane", word.display(crane));
+        assert!(word.parse("zzzzz", Lang::En).is_err());
+    }
+
+    #[test]
+    fn apply_sets_range_and_attempts() {
+        let config = Config {
+            difficulty: Some(crate::Difficulty::Normal),
+            ..crate::test_config(1, 100, 10)
+        };
+
+        let code = Variant::Code(3).apply(config.clone());
+        assert_eq!((0, 999, 10), (code.min, code.max, code.max_attempts));
+        assert_eq!(None, code.difficulty);
+
+        let word = Variant::Word.apply(config.clone());
+        assert_eq!(6, word.max_attempts);
+        assert_eq!(words().count() as u32 - 1, word.max);
+        assert_eq!(Some(String::from("word")), word.board());
+
+        let custom = Config {
+            max_attempts: 3,
+            difficulty: None,
+            ..config
+        };
+        assert_eq!(3, Variant::Word.apply(custom).max_attempts);
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt::Write;
+
+/// 命令行选项的唯一定义，`Config::new` 的解析、补全脚本和 man page 都从这里生成
+pub struct Opt {
+    pub short: Option<char>,
+    pub long: &'static str,
+    pub value: Option<&'static str>,
+    pub values: &'static [&'static str],
+    pub help: &'static str,
+}
+
+pub const OPTIONS: &[Opt] = &[
+    Opt {
+        short: None,
+        long: "stats",
+        value: None,
+        values: &[],
+        help: "Print per-file and total statistics after the search",
+    },
+    Opt {
+        short: Some('m'),
+        long: "max-count",
+        value: Some("NUM"),
+        values: &[],
+        help: "Stop reading a file after NUM matching lines",
+    },
+    Opt {
+        short: None,
+        long: "max-total",
+        value: Some("NUM"),
+        values: &[],
+        help: "Stop the whole search after NUM matching lines",
+    },
+    Opt {
+        short: None,
+        long: "max-filesize",
+        value: Some("SIZE"),
+        values: &[],
+        help: "Skip files larger than SIZE bytes (K, M and G suffixes allowed)",
+    },
+    Opt {
+        short: None,
+        long: "encoding",
+        value: Some("ENCODING"),
+        values: &["utf-8", "utf-16le", "utf-16be", "latin1"],
+        help: "Transcode input from ENCODING instead of detecting it from the BOM",
+    },
+    Opt {
+        short: None,
+        long: "pre",
+        value: Some("COMMAND"),
+        values: &[],
+        help: "Search the output of COMMAND PATH instead of each file's contents",
+    },
+    Opt {
+        short: None,
+        long: "pre-glob",
+        value: Some("GLOB"),
+        values: &[],
+        help: "Only run the --pre command on files matching GLOB (repeatable)",
+    },
+    Opt {
+        short: None,
+        long: "binary",
+        value: Some("POLICY"),
+        values: &["matches", "skip", "text"],
+        help: "How to treat files containing NUL bytes or invalid UTF-8: report a match, skip them, or search them as text",
+    },
+    Opt {
+        short: Some('a'),
+        long: "text",
+        value: None,
+        values: &[],
+        help: "Search binary and non-UTF-8 files as text, same as --binary text",
+    },
+    Opt {
+        short: Some('o'),
+        long: "only-matching",
+        value: None,
+        values: &[],
+        help: "Print only the matched part of each line, one match per line",
+    },
+    Opt {
+        short: None,
+        long: "format",
+        value: Some("TEMPLATE"),
+        values: &[],
+        help: "Print each result with TEMPLATE, using {path}, {line}, {col} and {text}",
+    },
+];
+
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+const SUBCOMMANDS: &[(&str, &str)] = &[
+    ("index", "Build a trigram index for a directory"),
+    ("completions", "Print a shell completion script"),
+    ("man", "Print the man page"),
+];
+
+const ENVIRONMENT: &[(&str, &str)] = &[(
+    "CASE_INSENSITIVE",
+    "When set, the query matches regardless of case",
+)];
+
+/// 按 `--long` 或 `-s` 查找选项
+pub fn find(arg: &str) -> Option<&'static Opt> {
+    if let Some(long) = arg.strip_prefix("--") {
+        return OPTIONS.iter().find(|opt| opt.long == long);
+    }
+
+    let mut chars = arg.strip_prefix('-')?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(short), None) => OPTIONS.iter().find(|opt| opt.short == Some(short)),
+        _ => None,
+    }
+}
+
+pub fn completions(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash()),
+        "zsh" => Some(zsh()),
+        "fish" => Some(fish()),
+        _ => None,
+    }
+}
+
+fn flags(opt: &Opt) -> Vec<String> {
+    let mut flags: Vec<String> = opt.short.iter().map(|c| format!("-{}", c)).collect();
+    flags.push(format!("--{}", opt.long));
+    flags
+}
+
+fn bash() -> String {
+    let mut script = String::from("_minigrep() {\n");
+    script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    script.push_str("    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n");
+
+    script.push_str("    case \"$prev\" in\n");
+    for opt in OPTIONS.iter().filter(|opt| opt.value.is_some()) {
+        let words = opt.values.join(" ");
+        let _ = writeln!(
+            script,
+            "        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return ;;",
+            flags(opt).join("|"),
+            words
+        );
+    }
+    script.push_str("        completions)\n");
+    let _ = writeln!(
+        script,
+        "            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return ;;",
+        SHELLS.join(" ")
+    );
+    script.push_str("    esac\n\n");
+
+    let all: Vec<String> = OPTIONS.iter().flat_map(flags).collect();
+    let _ = writeln!(
+        script,
+        "    if [[ \"$cur\" == -* ]]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n        return\n    fi",
+        all.join(" ")
+    );
+    let names: Vec<&str> = SUBCOMMANDS.iter().map(|(name, _)| *name).collect();
+    let _ = writeln!(
+        script,
+        "    if [[ $COMP_CWORD -eq 1 ]]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n        return\n    fi",
+        names.join(" ")
+    );
+    script.push_str("    COMPREPLY=($(compgen -f -- \"$cur\"))\n}\n\n");
+    script.push_str("complete -o filenames -F _minigrep minigrep\n");
+    script
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+fn zsh() -> String {
+    let mut script = String::from("#compdef minigrep\n\n_minigrep() {\n");
+    script.push_str("    if [[ $words[2] == completions ]]; then\n");
+    let _ = writeln!(
+        script,
+        "        _values 'shell' {}\n        return\n    fi\n",
+        SHELLS.join(" ")
+    );
+
+    script.push_str("    _arguments -s \\\n");
+    for opt in OPTIONS {
+        let help = zsh_escape(opt.help);
+        let value = match opt.value {
+            Some(name) if opt.values.is_empty() => format!(":{}: ", name),
+            Some(name) => format!(":{}:({})", name, opt.values.join(" ")),
+            None => String::new(),
+        };
+        let spec = match opt.short {
+            Some(short) => format!(
+                "'(-{0} --{1})'{{-{0},--{1}}}'[{2}]{3}'",
+                short, opt.long, help, value
+            ),
+            None => format!("'--{}[{}]{}'", opt.long, help, value),
+        };
+        let _ = writeln!(script, "        {} \\", spec);
+    }
+    script.push_str("        '1:query:' \\\n");
+    script.push_str("        '2:path:_files'\n}\n\n");
+    script.push_str("_minigrep \"$@\"\n");
+    script
+}
+
+fn fish() -> String {
+    let mut script = String::new();
+    for (name, help) in SUBCOMMANDS {
+        let _ = writeln!(
+            script,
+            "complete -c minigrep -n '__fish_use_subcommand' -a {} -d '{}'",
+            name, help
+        );
+    }
+    let _ = writeln!(
+        script,
+        "complete -c minigrep -n '__fish_seen_subcommand_from completions' -x -a '{}'",
+        SHELLS.join(" ")
+    );
+
+    for opt in OPTIONS {
+        let mut line = String::from("complete -c minigrep");
+        if let Some(short) = opt.short {
+            let _ = write!(line, " -s {}", short);
+        }
+        let _ = write!(line, " -l {}", opt.long);
+        if opt.value.is_some() {
+            line.push_str(if opt.values.is_empty() { " -r" } else { " -x" });
+        }
+        if !opt.values.is_empty() {
+            let _ = write!(line, " -a '{}'", opt.values.join(" "));
+        }
+        let _ = writeln!(script, "{} -d '{}'", line, opt.help.replace('\'', "\\'"));
+    }
+    script
+}
+
+fn roff(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('-', "\\-")
+}
+
+pub fn man_page() -> String {
+    let mut page = String::new();
+    let _ = writeln!(
+        page,
+        ".TH MINIGREP 1 \"\" \"minigrep {}\" \"User Commands\"",
+        env!("CARGO_PKG_VERSION")
+    );
+    page.push_str(".SH NAME\nminigrep \\- print lines containing a query string\n");
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(
+        ".B minigrep\n[\\fIOPTIONS\\fR] [\\fB\\-\\-\\fR] \\fIQUERY\\fR \\fIPATH\\fR\n.br\n",
+    );
+    page.push_str(".B minigrep index build\n\\fIDIR\\fR\n.br\n");
+    let _ = writeln!(
+        page,
+        ".B minigrep completions\n\\fI{}\\fR\n.br",
+        SHELLS.join("\\fR|\\fI")
+    );
+    page.push_str(".B minigrep man\n");
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(
+        "Prints every line of \\fIPATH\\fR that contains \\fIQUERY\\fR. \
+When \\fIPATH\\fR is a directory it is searched recursively and each line is \
+prefixed with the file it came from.\n",
+    );
+
+    page.push_str(".SH OPTIONS\n");
+    for opt in OPTIONS {
+        let flags: Vec<String> = flags(opt)
+            .iter()
+            .map(|flag| format!("\\fB{}\\fR", roff(flag)))
+            .collect();
+        let value = opt
+            .value
+            .map(|name| format!(" \\fI{}\\fR", name))
+            .unwrap_or_default();
+        let _ = writeln!(
+            page,
+            ".TP\n{}{}\n{}",
+            flags.join(", "),
+            value,
+            roff(opt.help)
+        );
+        if !opt.values.is_empty() {
+            let _ = writeln!(page, "One of: {}.", roff(&opt.values.join(", ")));
+        }
+    }
+
+    page.push_str(".SH COMMANDS\n");
+    for (name, help) in SUBCOMMANDS {
+        let _ = writeln!(page, ".TP\n.B {}\n{}", name, help);
+    }
+
+    page.push_str(".SH ENVIRONMENT\n");
+    for (name, help) in ENVIRONMENT {
+        let _ = writeln!(page, ".TP\n.B {}\n{}", roff(name), help);
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_long_and_short() {
+        assert_eq!("max-count", find("-m").unwrap().long);
+        assert_eq!("max-count", find("--max-count").unwrap().long);
+        assert!(find("-x").is_none());
+        assert!(find("--stat").is_none());
+        assert!(find("m").is_none());
+    }
+
+    #[test]
+    fn generated_docs_list_every_option() {
+        let page = man_page();
+        let bash = completions("bash").unwrap();
+        let zsh = completions("zsh").unwrap();
+        let fish = completions("fish").unwrap();
+
+        for opt in OPTIONS {
+            assert!(page.contains(&roff(&format!("--{}", opt.long))));
+            assert!(bash.contains(&format!("--{}", opt.long)));
+            assert!(zsh.contains(&format!("--{}", opt.long)));
+            assert!(fish.contains(&format!("-l {}", opt.long)));
+        }
+    }
+
+    #[test]
+    fn option_values_are_accepted() {
+        for name in find("--encoding").unwrap().values {
+            assert!(crate::Encoding::from_name(name).is_some(), "{}", name);
+        }
+        for name in find("--binary").unwrap().values {
+            assert!(crate::Binary::from_name(name).is_some(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl Encoding {
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Some(Encoding::Utf8),
+            "utf-16le" | "utf16le" | "utf-16" | "utf16" => Some(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Some(Encoding::Utf16Be),
+            "latin1" | "latin-1" | "iso-8859-1" | "iso8859-1" => Some(Encoding::Latin1),
+            _ => None,
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf16Be => &[0xFE, 0xFF],
+            Encoding::Latin1 => &[],
+        }
+    }
+
+    pub fn sniff(bytes: &[u8]) -> Option<Encoding> {
+        [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be]
+            .into_iter()
+            .find(|encoding| bytes.starts_with(encoding.bom()))
+    }
+}
+
+pub fn open(path: &Path, encoding: Option<Encoding>) -> io::Result<Box<dyn BufRead>> {
+    decode(Box::new(File::open(path)?), encoding)
+}
+
+/// 转码成 UTF-8：指定了 `encoding` 就按它解码，否则根据 BOM 判断，没有 BOM 时当作 UTF-8
+pub fn decode(input: Box<dyn Read>, encoding: Option<Encoding>) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(input);
+
+    let sniffed = Encoding::sniff(reader.fill_buf()?);
+    let encoding = encoding.or(sniffed).unwrap_or(Encoding::Utf8);
+    if sniffed == Some(encoding) {
+        reader.consume(encoding.bom().len());
+    }
+
+    Ok(match encoding {
+        Encoding::Utf8 => Box::new(reader),
+        _ => Box::new(BufReader::new(Decoder::new(reader, encoding))),
+    })
+}
+
+/// 把 UTF-16 / Latin-1 字节流边读边转成 UTF-8，无法解码的部分替换成 U+FFFD
+struct Decoder<R> {
+    inner: R,
+    encoding: Encoding,
+    raw: Vec<u8>,
+    decoded: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> Decoder<R> {
+    fn new(inner: R, encoding: Encoding) -> Decoder<R> {
+        Decoder {
+            inner,
+            encoding,
+            raw: Vec::new(),
+            decoded: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.pos = 0;
+
+        while self.decoded.is_empty() {
+            let mut chunk = [0; 8 * 1024];
+            let read = self.inner.read(&mut chunk)?;
+            let eof = read == 0;
+            self.raw.extend_from_slice(&chunk[..read]);
+            if eof && self.raw.is_empty() {
+                return Ok(());
+            }
+
+            let used = match self.encoding {
+                Encoding::Latin1 => {
+                    let text: String = self.raw.iter().map(|&b| b as char).collect();
+                    self.decoded.extend_from_slice(text.as_bytes());
+                    self.raw.len()
+                }
+                Encoding::Utf16Le | Encoding::Utf16Be => self.decode_utf16(eof),
+                Encoding::Utf8 => unreachable!("UTF-8 input is not decoded"),
+            };
+            self.raw.drain(..used);
+        }
+
+        Ok(())
+    }
+
+    fn decode_utf16(&mut self, eof: bool) -> usize {
+        let little = self.encoding == Encoding::Utf16Le;
+        let mut units: Vec<u16> = self
+            .raw
+            .chunks_exact(2)
+            .map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if little {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                }
+            })
+            .collect();
+
+        // 代理对可能被切在两块之间，留到下一次再解码
+        if !eof && matches!(units.last(), Some(0xD800..=0xDBFF)) {
+            units.pop();
+        }
+        let mut used = units.len() * 2;
+
+        let mut text: String = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+        if eof && used < self.raw.len() {
+            text.push(char::REPLACEMENT_CHARACTER);
+            used = self.raw.len();
+        }
+
+        self.decoded.extend_from_slice(text.as_bytes());
+        used
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.decoded.len() {
+            self.fill()?;
+        }
+
+        let available = &self.decoded[self.pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcode(bytes: &[u8], encoding: Encoding) -> String {
+        let mut text = String::new();
+        Decoder::new(bytes, encoding)
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn sniff_bom() {
+        assert_eq!(Some(Encoding::Utf8), Encoding::sniff(b"\xEF\xBB\xBFRust"));
+        assert_eq!(Some(Encoding::Utf16Le), Encoding::sniff(b"\xFF\xFER\0"));
+        assert_eq!(Some(Encoding::Utf16Be), Encoding::sniff(b"\xFE\xFF\0R"));
+        assert_eq!(None, Encoding::sniff(b"Rust"));
+    }
+
+    #[test]
+    fn utf16_with_surrogates() {
+        let text = "Rust: 🦀 安全\r\n";
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        assert_eq!(text, transcode(&le, Encoding::Utf16Le));
+        assert_eq!(text, transcode(&be, Encoding::Utf16Be));
+    }
+
+    #[test]
+    fn latin1() {
+        assert_eq!("café", transcode(b"caf\xE9", Encoding::Latin1));
+    }
+
+    #[test]
+    fn truncated_utf16() {
+        assert_eq!("R\u{FFFD}", transcode(b"R\0u", Encoding::Utf16Le));
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const INDEX_FILE: &str = ".minigrep-index";
+const HEADER: &str = "minigrep-index 1";
+
+struct Entry {
+    size: u64,
+    modified: (u64, u32),
+    trigrams: Vec<u64>,
+}
+
+pub struct Index {
+    root: PathBuf,
+    entries: HashMap<PathBuf, Entry>,
+}
+
+impl Index {
+    pub fn build(dir: &Path) -> Result<Index, Box<dyn Error>> {
+        let mut entries = HashMap::new();
+
+        for file in crate::walk(dir)? {
+            let relative = file.strip_prefix(dir)?.to_path_buf();
+            // 路径占一整行，含换行的路径无法存储，留给全量扫描
+            if relative.to_string_lossy().contains('\n') {
+                continue;
+            }
+            // 和搜索一样按 BOM 转码，无法解码的文件不建索引，搜索时照常扫描
+            let Ok(trigrams) = file_trigrams(&file) else {
+                continue;
+            };
+            let (size, modified) = stamp(&file)?;
+
+            entries.insert(
+                relative,
+                Entry {
+                    size,
+                    modified,
+                    trigrams,
+                },
+            );
+        }
+
+        Ok(Index {
+            root: dir.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn load(dir: &Path) -> Result<Option<Index>, Box<dyn Error>> {
+        let file = match File::open(dir.join(INDEX_FILE)) {
+            Ok(file) => file,
+            Err(_) => return Ok(None),
+        };
+        let mut lines = BufReader::new(file).lines();
+
+        if lines.next().transpose()?.as_deref() != Some(HEADER) {
+            return Err(format!("{} is not a minigrep index", INDEX_FILE).into());
+        }
+
+        let mut entries = HashMap::new();
+        while let Some(meta) = lines.next().transpose()? {
+            let grams = lines.next().transpose()?.ok_or("truncated index")?;
+            let mut fields = meta.splitn(4, ' ');
+            let mut field = || fields.next().ok_or("malformed index entry");
+
+            let size = field()?.parse()?;
+            let secs = field()?.parse()?;
+            let nanos = field()?.parse()?;
+            let path = PathBuf::from(field()?);
+            let trigrams = grams
+                .split_whitespace()
+                .map(|gram| u64::from_str_radix(gram, 16))
+                .collect::<Result<_, _>>()?;
+
+            entries.insert(
+                path,
+                Entry {
+                    size,
+                    modified: (secs, nanos),
+                    trigrams,
+                },
+            );
+        }
+
+        Ok(Some(Index {
+            root: dir.to_path_buf(),
+            entries,
+        }))
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let mut out = BufWriter::new(File::create(self.root.join(INDEX_FILE))?);
+        writeln!(out, "{}", HEADER)?;
+
+        let mut paths: Vec<_> = self.entries.keys().collect();
+        paths.sort();
+        for path in paths {
+            let entry = &self.entries[path];
+            writeln!(
+                out,
+                "{} {} {} {}",
+                entry.size,
+                entry.modified.0,
+                entry.modified.1,
+                path.display()
+            )?;
+            let grams: Vec<String> = entry.trigrams.iter().map(|g| format!("{:x}", g)).collect();
+            writeln!(out, "{}", grams.join(" "))?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 只有索引条目新鲜（大小和修改时间一致）且缺少查询的某个三元组时才返回 `true`
+    pub fn can_skip(&self, file: &Path, query: &str) -> bool {
+        let entry = match file
+            .strip_prefix(&self.root)
+            .ok()
+            .and_then(|relative| self.entries.get(relative))
+        {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        match stamp(file) {
+            Ok((size, modified)) if size == entry.size && modified == entry.modified => {}
+            _ => return false,
+        }
+
+        trigrams(query)
+            .iter()
+            .any(|gram| entry.trigrams.binary_search(gram).is_err())
+    }
+}
+
+fn stamp(file: &Path) -> Result<(u64, (u64, u32)), Box<dyn Error>> {
+    let metadata = fs::metadata(file)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?;
+    Ok((
+        metadata.len(),
+        (modified.as_secs(), modified.subsec_nanos()),
+    ))
+}
+
+/// 按字符小写后取三元组，大小写敏感和不敏感的搜索可以共用同一份索引
+pub fn trigrams(text: &str) -> Vec<u64> {
+    let mut grams = Trigrams::default();
+    grams.push(text);
+    grams.into_sorted()
+}
+
+/// 边读边收集三元组，内存只和不同三元组的个数有关；分块送进来时跨块的三元组也不会漏
+#[derive(Default)]
+struct Trigrams {
+    grams: HashSet<u64>,
+    /// 最近的字符，每个占 21 位
+    window: u64,
+    seen: usize,
+}
+
+impl Trigrams {
+    fn push(&mut self, text: &str) {
+        let chars = text
+            .chars()
+            .flat_map(char::to_lowercase)
+            // `str::to_lowercase` 会把词尾的 Σ 变成 ς，这里统一成 σ
+            .map(|c| if c == 'ς' { 'σ' } else { c });
+        for c in chars {
+            self.window = (self.window << 21 | c as u64) & ((1 << 63) - 1);
+            self.seen += 1;
+            if self.seen >= 3 {
+                self.grams.insert(self.window);
+            }
+        }
+    }
+
+    fn into_sorted(self) -> Vec<u64> {
+        let mut grams: Vec<u64> = self.grams.into_iter().collect();
+        grams.sort_unstable();
+        grams
+    }
+}
+
+/// 一块一块地读文件，块尾被截断的多字节字符留到下一块；不是合法 UTF-8 时返回错误
+fn file_trigrams(file: &Path) -> io::Result<Vec<u64>> {
+    let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+    let mut reader = crate::encoding::open(file, None)?;
+    let mut grams = Trigrams::default();
+    let mut pending = Vec::new();
+
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let read = chunk.len();
+        pending.extend_from_slice(chunk);
+        reader.consume(read);
+
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => return Err(invalid()),
+        };
+        grams.push(std::str::from_utf8(&pending[..valid]).map_err(|_| invalid())?);
+        pending.drain(..valid);
+    }
+
+    if !pending.is_empty() {
+        return Err(invalid());
+    }
+    Ok(grams.into_sorted())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigrams_ignore_case() {
+        assert_eq!(trigrams("Duct"), trigrams("dUCT"));
+        assert_eq!(2, trigrams("duct").len());
+    }
+
+    #[test]
+    fn trigrams_span_chunks() {
+        let text = "Σafe, fast, 生产力 productive";
+        let mut grams = Trigrams::default();
+        for piece in ["Σa", "fe, fast, 生", "产", "力 productive"] {
+            grams.push(piece);
+        }
+        assert_eq!(trigrams(text), grams.into_sorted());
+    }
+
+    #[test]
+    fn files_are_read_in_chunks() {
+        let path = std::env::temp_dir().join(format!("minigrep-trigrams-{}", std::process::id()));
+        // 多字节字符会跨过 BufReader 的 8 KiB 边界
+        let text = "生产力 duct tape\n".repeat(2000);
+        fs::write(&path, &text).unwrap();
+        let grams = file_trigrams(&path);
+        fs::write(&path, b"caf\xe9 au lait\n").unwrap();
+        let latin1 = file_trigrams(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(trigrams(&text), grams.unwrap());
+        assert!(latin1.is_err());
+    }
+
+    #[test]
+    fn short_query_has_no_trigrams() {
+        assert!(trigrams("du").is_empty());
+    }
+
+    #[test]
+    fn query_trigrams_are_subset_of_contents() {
+        let contents = trigrams("safe, fast, PRODUCTIVE.");
+        assert!(trigrams("duct")
+            .iter()
+            .all(|gram| contents.binary_search(gram).is_ok()));
+        assert!(trigrams("tape")
+            .iter()
+            .any(|gram| contents.binary_search(gram).is_err()));
+    }
+}
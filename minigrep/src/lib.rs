@@ -1,23 +1,159 @@
+use std::borrow::Cow;
 use std::env;
 use std::error::Error;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufRead, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use matcher::Matcher;
+
+pub mod cli;
+mod encoding;
+mod index;
+mod matcher;
+mod output;
+mod pre;
+mod stats;
+
+pub use encoding::Encoding;
+pub use index::Index;
+pub use matcher::{search_matches, Match};
+pub use output::Template;
+pub use stats::{FileStats, Stats};
+
+pub enum Command {
+    Search(Config),
+    BuildIndex(String),
+    Completions(String),
+    Man,
+}
+
+impl Command {
+    pub fn new(args: impl Iterator<Item = String>) -> Result<Command, &'static str> {
+        let args: Vec<String> = args.collect();
+
+        match args.as_slice() {
+            [_, command, action, dir] if command == "index" && action == "build" => {
+                Ok(Command::BuildIndex(dir.clone()))
+            }
+            [_, command, shell] if command == "completions" => {
+                if cli::SHELLS.contains(&shell.as_str()) {
+                    Ok(Command::Completions(shell.clone()))
+                } else {
+                    Err("Unknown shell, expected bash, zsh or fish")
+                }
+            }
+            [_, command] if command == "man" => Ok(Command::Man),
+            _ => Config::new(args.into_iter()).map(Command::Search),
+        }
+    }
+}
+
+/// 遇到二进制文件（含 NUL 字节）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binary {
+    Skip,
+    Matches,
+    Text,
+}
+
+impl Binary {
+    pub fn from_name(name: &str) -> Option<Binary> {
+        match name {
+            "skip" => Some(Binary::Skip),
+            "matches" => Some(Binary::Matches),
+            "text" => Some(Binary::Text),
+            _ => None,
+        }
+    }
+}
 
 pub struct Config {
     pub query: String,
     pub filename: String,
     pub case_sensitive: bool,
+    pub stats: bool,
+    pub max_count: Option<u64>,
+    pub max_total: Option<u64>,
+    pub max_filesize: Option<u64>,
+    pub encoding: Option<Encoding>,
+    pub pre: Option<String>,
+    pub pre_globs: Vec<String>,
+    pub binary: Binary,
+    pub only_matching: bool,
+    pub format: Option<Template>,
 }
 
 impl Config {
-    pub fn new(mut args: std::env::Args) -> Result<Config, &'static str> {
+    pub fn new(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
         args.next();
 
-        let query = match args.next() {
+        let mut stats = false;
+        let mut max_count = None;
+        let mut max_total = None;
+        let mut max_filesize = None;
+        let mut encoding = None;
+        let mut pre = None;
+        let mut pre_globs = Vec::new();
+        let mut binary = Binary::Matches;
+        let mut only_matching = false;
+        let mut format = None;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                positional.extend(args.by_ref());
+                continue;
+            }
+            let opt = match cli::find(&arg) {
+                Some(opt) => opt,
+                None if arg.starts_with('-') && arg.len() > 1 => return Err("Unknown option"),
+                None => {
+                    positional.push(arg);
+                    continue;
+                }
+            };
+            let value = match opt.value {
+                Some(_) => args.next().ok_or("Missing option value")?,
+                None => String::new(),
+            };
+
+            match opt.long {
+                "stats" => stats = true,
+                "max-count" => {
+                    max_count = Some(value.parse().map_err(|_| "Invalid number for -m")?);
+                }
+                "max-total" => {
+                    max_total = Some(
+                        value
+                            .parse()
+                            .map_err(|_| "Invalid number for --max-total")?,
+                    );
+                }
+                "max-filesize" => {
+                    max_filesize =
+                        Some(parse_size(&value).ok_or("Invalid size for --max-filesize")?);
+                }
+                "encoding" => {
+                    encoding = Some(Encoding::from_name(&value).ok_or("Unknown encoding")?);
+                }
+                "pre" => pre = Some(value),
+                "pre-glob" => pre_globs.push(value),
+                "binary" => binary = Binary::from_name(&value).ok_or("Unknown binary policy")?,
+                "text" => binary = Binary::Text,
+                "only-matching" => only_matching = true,
+                "format" => format = Some(Template::parse(&value)?),
+                _ => unreachable!("option --{} is not handled", opt.long),
+            }
+        }
+        let mut positional = positional.into_iter();
+
+        let query = match positional.next() {
             Some(arg) => arg,
             None => return Err("Didn't get a query string"),
         };
 
-        let filename = match args.next() {
+        let filename = match positional.next() {
             Some(arg) => arg,
             None => return Err("Didn't get a file name"),
         };
@@ -28,24 +164,256 @@ impl Config {
             query,
             filename,
             case_sensitive,
+            stats,
+            max_count,
+            max_total,
+            max_filesize,
+            encoding,
+            pre,
+            pre_globs,
+            binary,
+            only_matching,
+            format,
         })
     }
+
+    fn preprocesses(&self, path: &Path) -> bool {
+        self.pre.is_some() && pre::applies(&self.pre_globs, path)
+    }
 }
 
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+    let path = Path::new(&config.filename);
+    let matcher = Matcher::new(&config.query, config.case_sensitive);
+    let mut stats = Stats::new();
+
+    let files = if path.is_dir() {
+        walk(path)?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    // 索引按 BOM 自动识别编码建立，手动指定编码时不能信任它
+    let index = if path.is_dir() && config.encoding.is_none() {
+        // 索引损坏、被截断或者版本不对时只是少了加速，照常全量扫描
+        Index::load(path).unwrap_or_else(|err| {
+            eprintln!("Warning: {}; searching without the index", err);
+            None
+        })
+    } else {
+        None
+    };
+
+    for file in &files {
+        let remaining = config
+            .max_total
+            .map(|max| max.saturating_sub(stats.matching_lines()));
+        if remaining == Some(0) {
+            break;
+        }
+
+        if let Some(index) = &index {
+            // 预处理后的内容和索引里的原文不同，不能用索引跳过
+            if !config.preprocesses(file) && index.can_skip(file, &config.query) {
+                stats.skipped += 1;
+                continue;
+            }
+        }
+
+        let limit = match (config.max_count, remaining) {
+            (Some(count), Some(remaining)) => Some(count.min(remaining)),
+            (count, remaining) => count.or(remaining),
+        };
+        let searched = check_size(file, &config, &mut stats).and_then(|fits| {
+            if fits {
+                search_file(&matcher, file, &config, path.is_dir(), limit, &mut stats)?;
+            }
+            Ok(())
+        });
+        match searched {
+            Ok(()) => {}
+            // 搜索目录时一个文件打不开或读不了不影响其他文件
+            Err(err) if path.is_dir() => eprintln!("Warning: {}: {}", file.display(), err),
+            Err(err) => return Err(format!("{}: {}", file.display(), err).into()),
+        }
+    }
+
+    if config.stats {
+        print!("{}", stats);
+    }
+
+    Ok(())
+}
+
+/// 超过 `--max-filesize` 时记下来并返回 `false`
+fn check_size(file: &Path, config: &Config, stats: &mut Stats) -> Result<bool, Box<dyn Error>> {
+    match config.max_filesize {
+        Some(max) if fs::metadata(file)?.len() > max => {
+            stats.too_large += 1;
+            Ok(false)
+        }
+        _ => Ok(true),
+    }
+}
+
+/// 逐行读取，匹配行数达到 `limit` 后立即停止读取
+fn search_file(
+    matcher: &Matcher,
+    path: &Path,
+    config: &Config,
+    with_path: bool,
+    limit: Option<u64>,
+    stats: &mut Stats,
+) -> Result<(), Box<dyn Error>> {
+    let input: Box<dyn Read> = match &config.pre {
+        Some(command) if config.preprocesses(path) => {
+            Box::new(pre::Preprocessed::spawn(command, path)?)
+        }
+        _ => Box::new(File::open(path)?),
+    };
+    let mut reader = encoding::decode(input, config.encoding)?;
+    let file = stats.start_file(path);
+    let mut buf = Vec::new();
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
+    // 和 grep 一样，开头一块里有 NUL 字节就当作二进制文件
+    file.binary = reader.fill_buf()?.contains(&0);
+
+    while limit.is_none_or(|limit| file.matching_lines < limit) {
+        if file.binary && config.binary == Binary::Skip {
+            break;
+        }
+
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        file.bytes += read as u64;
+        file.lines += 1;
+        file.binary = file.binary || buf.contains(&0);
+        // NUL 可能出现在开头一块之后，这一行也不能再匹配输出
+        if file.binary && config.binary == Binary::Skip {
+            break;
+        }
+
+        let line = match std::str::from_utf8(&buf) {
+            Ok(line) => Cow::Borrowed(line),
+            Err(_) if config.binary == Binary::Text => String::from_utf8_lossy(&buf),
+            // 和 grep 一样，不是合法 UTF-8 的文件也当作二进制文件，按 --binary 处理
+            Err(_) => {
+                file.binary = true;
+                if config.binary == Binary::Skip {
+                    break;
+                }
+                String::from_utf8_lossy(&buf)
+            }
+        };
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        let spans = matcher.find(line);
+        if spans.is_empty() {
+            continue;
+        }
+        file.matching_lines += 1;
+        file.matches += spans.len() as u64;
+
+        if file.binary && config.binary == Binary::Matches {
+            println!("Binary file {} matches", path.display());
+            break;
+        }
+        print_match(config, path, with_path, file.lines, line, &spans);
+    }
+
+    Ok(())
+}
+
+/// `-o` 时每个匹配单独输出一次，否则每行输出一次，列号取第一个匹配
+fn print_match(
+    config: &Config,
+    path: &Path,
+    with_path: bool,
+    line_number: u64,
+    line: &str,
+    spans: &[Range<usize>],
+) {
+    let pieces: Vec<(usize, &str)> = if config.only_matching {
+        spans
+            .iter()
+            .filter(|span| !span.is_empty())
+            .map(|span| (span.start + 1, &line[span.clone()]))
+            .collect()
     } else {
-        search_insensitive(&config.query, &contents)
+        vec![(spans[0].start + 1, line)]
+    };
+
+    for (col, text) in pieces {
+        match &config.format {
+            Some(template) => {
+                println!("{}", template.render(path, line_number as usize, col, text))
+            }
+            None if with_path => println!("{}:{}", path.display(), text),
+            None => println!("{}", text),
+        }
+    }
+}
+
+pub fn build_index(dir: &str) -> Result<(), Box<dyn Error>> {
+    let index = Index::build(Path::new(dir))?;
+    index.save()?;
+    println!("Indexed {} files in {}", index.len(), dir);
+
+    Ok(())
+}
+
+/// 解析文件大小，支持 K/M/G 后缀（1024 进制）
+fn parse_size(value: &str) -> Option<u64> {
+    let (number, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => value.split_at(i),
+        None => (value, ""),
+    };
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return None,
     };
 
-    for line in results {
-        println!("{}", line)
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// 递归列出目录下的所有普通文件，按路径排序，跳过索引文件本身
+///
+/// 不进入指向目录的符号链接（避免循环），指向文件的符号链接照常搜索，
+/// 断开的链接、管道等不是普通文件的直接跳过；读不了的子目录提示后跳过
+pub fn walk(root: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if dir != root => {
+                eprintln!("Warning: {}: {}", dir.display(), err);
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if entry.file_name() != index::INDEX_FILE
+                && fs::metadata(&path).is_ok_and(|metadata| metadata.is_file())
+            {
+                files.push(path);
+            }
+        }
     }
 
-    return Ok(());
+    files.sort();
+    Ok(files)
 }
 
 pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
@@ -105,4 +473,54 @@ Trust me";
             search_insensitive(query, contents)
         );
     }
+
+    #[test]
+    fn stats_flag_anywhere() {
+        let args = ["minigrep", "to", "--stats", "poem.txt"].map(String::from);
+        let config = Config::new(args.into_iter()).unwrap();
+
+        assert!(config.stats);
+        assert_eq!("to", config.query);
+        assert_eq!("poem.txt", config.filename);
+    }
+
+    #[test]
+    fn limits_parse() {
+        let args = [
+            "minigrep",
+            "-m",
+            "2",
+            "--max-filesize",
+            "10K",
+            "to",
+            "poem.txt",
+        ];
+        let config = Config::new(args.map(String::from).into_iter()).unwrap();
+
+        assert_eq!(Some(2), config.max_count);
+        assert_eq!(None, config.max_total);
+        assert_eq!(Some(10 * 1024), config.max_filesize);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(Some(512), parse_size("512"));
+        assert_eq!(Some(3 << 20), parse_size("3M"));
+        assert_eq!(Some(1 << 30), parse_size("1gb"));
+        assert_eq!(None, parse_size("12X"));
+        assert_eq!(None, parse_size("M"));
+    }
+
+    #[test]
+    fn every_documented_option_is_parsed() {
+        for opt in cli::OPTIONS {
+            let mut args = vec![String::from("minigrep"), format!("--{}", opt.long)];
+            if opt.value.is_some() {
+                args.push(opt.values.first().unwrap_or(&"1").to_string());
+            }
+            args.extend(["to", "poem.txt"].map(String::from));
+
+            assert!(Config::new(args.into_iter()).is_ok(), "--{}", opt.long);
+        }
+    }
 }
@@ -1,14 +1,27 @@
-use minigrep::Config;
+use minigrep::{cli, Command};
 use std::env;
 use std::process;
 
 fn main() {
-    let config = Config::new(env::args()).unwrap_or_else(|err| {
+    let command = Command::new(env::args()).unwrap_or_else(|err| {
         eprintln!("Problem parsing arguments: {}", err);
         process::exit(1);
     });
 
-    if let Err(err) = minigrep::run(config) {
+    let result = match command {
+        Command::Search(config) => minigrep::run(config),
+        Command::BuildIndex(dir) => minigrep::build_index(&dir),
+        Command::Completions(shell) => {
+            print!("{}", cli::completions(&shell).unwrap());
+            Ok(())
+        }
+        Command::Man => {
+            print!("{}", cli::man_page());
+            Ok(())
+        }
+    };
+
+    if let Err(err) = result {
         eprintln!("Application error: {}", err);
         process::exit(1);
     }
@@ -0,0 +1,105 @@
+use std::ops::Range;
+
+pub struct Matcher {
+    query: String,
+    case_sensitive: bool,
+}
+
+/// 一行中的所有匹配，`spans` 是原始行里的字节区间
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match<'a> {
+    pub line_number: usize,
+    pub line: &'a str,
+    pub spans: Vec<Range<usize>>,
+}
+
+impl Matcher {
+    pub fn new(query: &str, case_sensitive: bool) -> Matcher {
+        let query = if case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+
+        Matcher {
+            query,
+            case_sensitive,
+        }
+    }
+
+    /// 空查询匹配每一行，记为行首的一个空区间
+    pub fn find(&self, line: &str) -> Vec<Range<usize>> {
+        if self.query.is_empty() {
+            return std::iter::once(0..0).collect();
+        }
+        if self.case_sensitive {
+            return line
+                .match_indices(&self.query)
+                .map(|(start, found)| start..start + found.len())
+                .collect();
+        }
+
+        // 小写后字节长度可能变化，记下每个小写字节对应的原始字符位置
+        let lowered = line.to_lowercase();
+        let mut origin = Vec::with_capacity(lowered.len() + 1);
+        for (start, c) in line.char_indices() {
+            let len: usize = c.to_lowercase().map(char::len_utf8).sum();
+            origin.extend(std::iter::repeat_n((start, start + c.len_utf8()), len));
+        }
+
+        lowered
+            .match_indices(&self.query)
+            .map(|(start, found)| origin[start].0..origin[start + found.len() - 1].1)
+            .collect()
+    }
+}
+
+pub fn search_matches<'a>(query: &str, contents: &'a str, case_sensitive: bool) -> Vec<Match<'a>> {
+    let matcher = Matcher::new(query, case_sensitive);
+
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans = matcher.find(line);
+            (!spans.is_empty()).then_some(Match {
+                line_number: i + 1,
+                line,
+                spans,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_case_sensitive() {
+        let matches = search_matches("ru", "Rust:\ntrust rules", true);
+
+        assert_eq!(1, matches.len());
+        assert_eq!(2, matches[0].line_number);
+        assert_eq!(vec![1..3, 6..8], matches[0].spans);
+    }
+
+    #[test]
+    fn spans_case_insensitive_map_back_to_original_bytes() {
+        // 'İ' 小写后变成两个字符，后面的偏移都会错开
+        let line = "İstanbul RUST";
+        let spans = Matcher::new("rust", false).find(line);
+
+        assert_eq!(1, spans.len());
+        assert_eq!(10..14, spans[0]);
+        assert_eq!("RUST", &line[spans[0].clone()]);
+    }
+
+    #[test]
+    fn empty_query_matches_every_line() {
+        let spans = Matcher::new("", true).find("anything");
+
+        assert_eq!(1, spans.len());
+        assert!(spans[0].is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+use std::path::Path;
+
+/// `--format` 模板，支持 {path} {line} {col} {text}，`{{` 和 `}}` 输出花括号
+#[derive(Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Path,
+    Line,
+    Col,
+    Text,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, &'static str> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err("Unclosed { in --format"),
+                        }
+                    }
+                    let part = match name.as_str() {
+                        "path" => Part::Path,
+                        "line" => Part::Line,
+                        "col" => Part::Col,
+                        "text" => Part::Text,
+                        _ => return Err("Unknown --format placeholder"),
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Template { parts })
+    }
+
+    /// `line` 和 `col` 都从 1 开始，`col` 是字节列（和 vim 的 quickfix 一致）
+    pub fn render(&self, path: &Path, line: usize, col: usize, text: &str) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            let _ = match part {
+                Part::Literal(literal) => write!(out, "{}", literal),
+                Part::Path => write!(out, "{}", path.display()),
+                Part::Line => write!(out, "{}", line),
+                Part::Col => write!(out, "{}", col),
+                Part::Text => write!(out, "{}", text),
+            };
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_quickfix() {
+        let template = Template::parse("{path}:{line}:{col}: {text}").unwrap();
+
+        assert_eq!(
+            "poem.txt:3:5: nobody",
+            template.render(Path::new("poem.txt"), 3, 5, "nobody")
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        let template = Template::parse("{{{line}}}").unwrap();
+
+        assert_eq!("{7}", template.render(Path::new("a"), 7, 1, ""));
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        assert!(Template::parse("{file}").is_err());
+        assert!(Template::parse("{path").is_err());
+    }
+}
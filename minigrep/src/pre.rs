@@ -0,0 +1,134 @@
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, ExitStatus, Stdio};
+
+/// 没有 `--pre-glob` 时预处理所有文件，否则只处理匹配任一 glob 的文件
+pub fn applies(globs: &[String], path: &Path) -> bool {
+    if globs.is_empty() {
+        return true;
+    }
+
+    let full = path.to_string_lossy();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    globs.iter().any(|glob| {
+        let text = if glob.contains('/') { &full } else { &name };
+        glob_match(glob, text)
+    })
+}
+
+/// 支持 `*` 和 `?` 的简单 glob
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // 让上一个 `*` 多吞一个字符再试
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 运行 `COMMAND PATH`，把子进程的 stdout 当作文件内容读取
+pub struct Preprocessed {
+    command: String,
+    child: Child,
+    stdout: ChildStdout,
+    status: Option<ExitStatus>,
+}
+
+impl Preprocessed {
+    pub fn spawn(command: &str, path: &Path) -> io::Result<Preprocessed> {
+        let mut child = Command::new(command)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                io::Error::new(err.kind(), format!("failed to run {}: {}", command, err))
+            })?;
+        let stdout = child.stdout.take().unwrap();
+
+        Ok(Preprocessed {
+            command: command.to_string(),
+            child,
+            stdout,
+            status: None,
+        })
+    }
+}
+
+impl Read for Preprocessed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stdout.read(buf)?;
+        if read == 0 && !buf.is_empty() && self.status.is_none() {
+            let status = self.child.wait()?;
+            self.status = Some(status);
+            if !status.success() {
+                return Err(io::Error::other(format!(
+                    "{} failed: {}",
+                    self.command, status
+                )));
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+impl Drop for Preprocessed {
+    // 达到 -m 等限制提前停止时，不再等子进程输出完
+    fn drop(&mut self) {
+        if self.status.is_none() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("*.pdf", "report.pdf"));
+        assert!(glob_match("a?c*", "abcdef"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("*.pdf", "report.pdf.txt"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn globs_match_name_unless_they_contain_a_slash() {
+        let path = Path::new("docs/2022/report.pdf");
+        let globs = |globs: &[&str]| globs.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+
+        assert!(applies(&[], path));
+        assert!(applies(&globs(&["*.txt", "*.pdf"]), path));
+        assert!(applies(&globs(&["docs/*"]), path));
+        assert!(!applies(&globs(&["docs*"]), path));
+    }
+}
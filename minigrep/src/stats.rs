@@ -0,0 +1,113 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub struct FileStats {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub lines: u64,
+    pub matches: u64,
+    pub matching_lines: u64,
+    pub binary: bool,
+}
+
+pub struct Stats {
+    pub files: Vec<FileStats>,
+    pub skipped: u64,
+    pub too_large: u64,
+    started: Instant,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats {
+            files: Vec::new(),
+            skipped: 0,
+            too_large: 0,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn start_file(&mut self, path: &Path) -> &mut FileStats {
+        self.files.push(FileStats {
+            path: path.to_path_buf(),
+            bytes: 0,
+            lines: 0,
+            matches: 0,
+            matching_lines: 0,
+            binary: false,
+        });
+        self.files.last_mut().unwrap()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.bytes).sum()
+    }
+
+    pub fn lines(&self) -> u64 {
+        self.files.iter().map(|file| file.lines).sum()
+    }
+
+    pub fn matches(&self) -> u64 {
+        self.files.iter().map(|file| file.matches).sum()
+    }
+
+    pub fn matching_lines(&self) -> u64 {
+        self.files.iter().map(|file| file.matching_lines).sum()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats::new()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elapsed = self.elapsed();
+        let seconds = elapsed.as_secs_f64();
+        // 耗时太短时吞吐量没有意义
+        let throughput = if seconds > 0.0 {
+            self.bytes() as f64 / seconds / (1024.0 * 1024.0)
+        } else {
+            0.0
+        };
+
+        writeln!(f)?;
+        for file in &self.files {
+            writeln!(
+                f,
+                "{}: {} matches in {} lines ({} lines, {} bytes{})",
+                file.path.display(),
+                file.matches,
+                file.matching_lines,
+                file.lines,
+                file.bytes,
+                if file.binary { ", binary" } else { "" }
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "files scanned:  {}", self.files.len())?;
+        if self.skipped > 0 {
+            writeln!(f, "files skipped:  {} (index)", self.skipped)?;
+        }
+        if self.too_large > 0 {
+            writeln!(f, "files skipped:  {} (too large)", self.too_large)?;
+        }
+        let binary = self.files.iter().filter(|file| file.binary).count();
+        if binary > 0 {
+            writeln!(f, "binary files:   {}", binary)?;
+        }
+        writeln!(f, "bytes read:     {}", self.bytes())?;
+        writeln!(f, "lines examined: {}", self.lines())?;
+        writeln!(f, "matches:        {}", self.matches())?;
+        writeln!(f, "matching lines: {}", self.matching_lines())?;
+        writeln!(f, "elapsed:        {:.3?}", elapsed)?;
+        writeln!(f, "throughput:     {:.2} MiB/s", throughput)
+    }
+}
@@ -0,0 +1,433 @@
+use std::fs;
+use std::process::Command;
+
+mod common;
+
+use common::{minigrep, minigrep_in, scratch, stderr, stdout};
+
+#[test]
+fn single_file() {
+    let output = minigrep(&["to", "../../poem.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        "Are you nobody, too?\nHow dreary to be somebody!\n",
+        stdout(&output)
+    );
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn case_insensitive_env() {
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["rUsT", "tree/notes.txt"])
+        .current_dir(common::fixtures())
+        .env("CASE_INSENSITIVE", "1")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!("Rust:\nTrust me\n", stdout(&output));
+}
+
+#[test]
+fn recursive_search_prefixes_paths() {
+    let output = minigrep(&["duct", "tree"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        "tree/nested/duct.txt:productive\ntree/notes.txt:safe, fast, productive.\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn missing_arguments() {
+    let output = minigrep(&["to"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert_eq!(
+        "Problem parsing arguments: Didn't get a file name\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn unknown_option() {
+    let output = minigrep(&["--nope", "to", "tree"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "Problem parsing arguments: Unknown option\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn missing_file() {
+    let output = minigrep(&["to", "no-such-file.txt"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).starts_with("Application error: no-such-file.txt: "));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_do_not_stop_the_walk() {
+    use std::os::unix::fs::symlink;
+
+    let dir = scratch("symlinks_do_not_stop_the_walk");
+    symlink("no-such-file.txt", dir.join("a-broken")).unwrap();
+    symlink("nested", dir.join("b-dir-link")).unwrap();
+    symlink("nested/duct.txt", dir.join("c-file-link")).unwrap();
+
+    for args in [&["tape", "."][..], &["--max-filesize", "1M", "tape", "."]] {
+        let output = minigrep_in(&dir, args);
+        assert!(output.status.success());
+        assert_eq!(
+            "./c-file-link:Duct tape\n./nested/duct.txt:Duct tape\n",
+            stdout(&output)
+        );
+        assert_eq!("", stderr(&output));
+    }
+
+    assert!(minigrep_in(&dir, &["index", "build", "."]).status.success());
+}
+
+#[test]
+fn double_dash_allows_dashed_query() {
+    let output = minigrep(&["--", "-", "../../poem.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("Then there's a pair of us - don't tell!\n", stdout(&output));
+}
+
+#[test]
+fn index_build_and_search() {
+    let dir = scratch("index_build_and_search");
+
+    let output = minigrep_in(&dir, &["index", "build", "."]);
+    assert!(output.status.success());
+    assert_eq!("Indexed 3 files in .\n", stdout(&output));
+    assert!(dir.join(".minigrep-index").exists());
+
+    let output = minigrep_in(&dir, &["--stats", "tape", "."]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.starts_with("./nested/duct.txt:Duct tape\n"));
+    assert!(out.contains("files scanned:  1\n"));
+    assert!(out.contains("files skipped:  2 (index)\n"));
+}
+
+#[test]
+fn invalid_utf8_does_not_stop_the_walk() {
+    let dir = scratch("invalid_utf8_does_not_stop_the_walk");
+    fs::write(dir.join("a-latin1.txt"), b"caf\xe9 tape\n").unwrap();
+
+    let output = minigrep_in(&dir, &["tape", "."]);
+    assert!(output.status.success());
+    assert_eq!(
+        "Binary file ./a-latin1.txt matches\n./nested/duct.txt:Duct tape\n",
+        stdout(&output)
+    );
+    assert_eq!("", stderr(&output));
+
+    let output = minigrep_in(&dir, &["--binary", "skip", "tape", "."]);
+    assert_eq!("./nested/duct.txt:Duct tape\n", stdout(&output));
+}
+
+#[test]
+fn stale_index_entries_are_rescanned() {
+    let dir = scratch("stale_index_entries_are_rescanned");
+    assert!(minigrep_in(&dir, &["index", "build", "."]).status.success());
+
+    fs::write(dir.join("notes.txt"), "masking tape\n").unwrap();
+
+    let output = minigrep_in(&dir, &["tape", "."]);
+    assert_eq!(
+        "./nested/duct.txt:Duct tape\n./notes.txt:masking tape\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn corrupt_index_is_reported() {
+    let dir = scratch("corrupt_index_is_reported");
+    fs::write(dir.join(".minigrep-index"), "garbage\n").unwrap();
+
+    let output = minigrep_in(&dir, &["tape", "."]);
+    assert!(output.status.success());
+    assert_eq!("./nested/duct.txt:Duct tape\n", stdout(&output));
+    assert_eq!(
+        "Warning: .minigrep-index is not a minigrep index; searching without the index\n",
+        stderr(&output)
+    );
+
+    // 被截断的索引也一样：提示之后照常搜索
+    fs::write(
+        dir.join(".minigrep-index"),
+        "minigrep-index 1\n12 0 0 notes.txt\n",
+    )
+    .unwrap();
+    let output = minigrep_in(&dir, &["tape", "."]);
+    assert!(output.status.success());
+    assert_eq!("./nested/duct.txt:Duct tape\n", stdout(&output));
+    assert_eq!(
+        "Warning: truncated index; searching without the index\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn stats_report() {
+    let output = minigrep(&["--stats", "duct", "tree/notes.txt"]);
+
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.starts_with("safe, fast, productive.\n\n"));
+    assert!(out.contains("tree/notes.txt: 1 matches in 1 lines (4 lines, 51 bytes)\n"));
+    assert!(out.contains("lines examined: 4\n"));
+    assert!(out.contains("throughput:"));
+}
+
+#[test]
+fn max_count_per_file() {
+    let output = minigrep(&["-m", "1", "o", "../../poem.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("I'm nobody! Who are you?\n", stdout(&output));
+}
+
+#[test]
+fn max_total_stops_the_search() {
+    let output = minigrep(&["--max-total", "1", "--stats", "duct", "tree"]);
+
+    let out = stdout(&output);
+    assert!(out.starts_with("tree/nested/duct.txt:productive\n\n"));
+    assert!(out.contains("files scanned:  1\n"));
+}
+
+#[test]
+fn max_filesize_skips_large_files() {
+    let output = minigrep(&["--max-filesize", "30", "--stats", "duct", "tree"]);
+
+    let out = stdout(&output);
+    assert!(out.starts_with("tree/nested/duct.txt:productive\n\n"));
+    assert!(out.contains("files skipped:  2 (too large)\n"));
+}
+
+#[test]
+fn invalid_limit() {
+    let output = minigrep(&["-m", "many", "o", "tree"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "Problem parsing arguments: Invalid number for -m\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn utf16_bom_is_transcoded() {
+    let output = minigrep(&["UTF-16", "tree/utf16.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("Rust in UTF-16\n", stdout(&output));
+}
+
+#[test]
+fn latin1_needs_encoding() {
+    // 不指定编码时 Latin-1 不是合法的 UTF-8，当作二进制文件
+    let output = minigrep(&["café", "latin1.txt"]);
+    assert!(output.status.success());
+    assert_eq!("", stdout(&output));
+
+    let output = minigrep(&["au lait", "latin1.txt"]);
+    assert!(output.status.success());
+    assert_eq!("Binary file latin1.txt matches\n", stdout(&output));
+
+    let output = minigrep(&["--encoding", "latin1", "café", "latin1.txt"]);
+    assert!(output.status.success());
+    assert_eq!("café au lait\n", stdout(&output));
+}
+
+#[test]
+fn completions_for_each_shell() {
+    for shell in ["bash", "zsh", "fish"] {
+        let output = minigrep(&["completions", shell]);
+        assert!(output.status.success());
+        assert!(stdout(&output).contains("max-count"));
+    }
+
+    let output = minigrep(&["completions", "tcsh"]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "Problem parsing arguments: Unknown shell, expected bash, zsh or fish\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn man_page() {
+    let output = minigrep(&["man"]);
+
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with(".TH MINIGREP 1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_command_output_is_searched() {
+    let output = minigrep(&["--pre", "./upper.sh", "DUCT", "tree/notes.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("SAFE, FAST, PRODUCTIVE.\n", stdout(&output));
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_glob_limits_preprocessed_files() {
+    let output = minigrep(&[
+        "--pre",
+        "./upper.sh",
+        "--pre-glob",
+        "duct.*",
+        "DUCT",
+        "tree",
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        "tree/nested/duct.txt:DUCT TAPE\ntree/nested/duct.txt:PRODUCTIVE\n",
+        stdout(&output)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_command_is_stopped_at_limits() {
+    // `yes` 会一直输出，只有提前停止读取并结束子进程才能退出
+    let output = minigrep(&["--pre", "yes", "-m", "2", "notes", "tree/notes.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("tree/notes.txt\ntree/notes.txt\n", stdout(&output));
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_pre_command() {
+    let output = minigrep(&["--pre", "false", "to", "tree/notes.txt"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("false failed"));
+
+    let output = minigrep(&["--pre", "./no-such-command", "to", "tree/notes.txt"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("failed to run ./no-such-command"));
+}
+
+#[test]
+fn binary_file_matches_by_default() {
+    let output = minigrep(&["rust", "binary/data.bin"]);
+    assert!(output.status.success());
+    assert_eq!("Binary file binary/data.bin matches\n", stdout(&output));
+
+    let output = minigrep(&["rust", "binary"]);
+    assert_eq!(
+        "Binary file binary/data.bin matches\nbinary/plain.txt:rust plain\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn binary_files_can_be_skipped() {
+    let output = minigrep(&["--binary", "skip", "rust", "binary/data.bin"]);
+    assert!(output.status.success());
+    assert_eq!("", stdout(&output));
+
+    let output = minigrep(&["--binary", "skip", "--stats", "rust", "binary"]);
+    let out = stdout(&output);
+    assert!(out.starts_with("binary/plain.txt:rust plain\n\n"));
+    assert!(out.contains("binary/data.bin: 0 matches in 0 lines (0 lines, 0 bytes, binary)\n"));
+}
+
+#[test]
+fn nul_past_the_first_block_is_binary() {
+    let dir = scratch("nul_past_the_first_block_is_binary");
+    let mut contents = b"tape early\n".to_vec();
+    contents.extend(std::iter::repeat_n(b'x', 9000));
+    contents.extend(b"\ntape \0 late\n");
+    fs::write(dir.join("late.bin"), contents).unwrap();
+
+    let output = minigrep_in(&dir, &["--binary", "skip", "tape", "late.bin"]);
+    assert!(output.status.success());
+    assert_eq!("tape early\n", stdout(&output));
+
+    let output = minigrep_in(&dir, &["--binary", "matches", "tape", "late.bin"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "tape early\nBinary file late.bin matches\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn binary_files_as_text() {
+    let output = minigrep(&["-a", "more", "binary/data.bin"]);
+    assert!(output.status.success());
+    assert_eq!("more rust\n", stdout(&output));
+
+    let output = minigrep(&["--binary", "text", "more", "binary"]);
+    assert_eq!("binary/data.bin:more rust\n", stdout(&output));
+}
+
+#[test]
+fn text_mode_reads_invalid_utf8_lossily() {
+    let output = minigrep(&["-a", "au lait", "latin1.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("caf\u{FFFD} au lait\n", stdout(&output));
+}
+
+#[test]
+fn only_matching() {
+    let output = minigrep(&["-o", "bod", "../../poem.txt"]);
+
+    assert!(output.status.success());
+    assert_eq!("bod\nbod\nbod\n", stdout(&output));
+}
+
+#[test]
+fn quickfix_format() {
+    let output = minigrep(&["--format", "{path}:{line}:{col}: {text}", "duct", "tree"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        "tree/nested/duct.txt:2:4: productive\ntree/notes.txt:2:16: safe, fast, productive.\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn format_with_only_matching_prints_every_match() {
+    let output = minigrep(&[
+        "-o",
+        "--format",
+        "{line}:{col}:{text}",
+        "o",
+        "tree/notes.txt",
+    ]);
+
+    assert_eq!("2:15:o\n", stdout(&output));
+}
+
+#[test]
+fn invalid_format() {
+    let output = minigrep(&["--format", "{file}", "to", "tree"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "Problem parsing arguments: Unknown --format placeholder\n",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,67 @@
+use minigrep::{search, search_insensitive, search_matches};
+use proptest::prelude::*;
+
+/// 参考实现：逐字符转小写后朴素地逐个位置比较
+fn reference_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+
+    contents
+        .lines()
+        .filter(|line| {
+            let line: Vec<char> = line.chars().flat_map(char::to_lowercase).collect();
+            query.is_empty() || line.windows(query.len()).any(|window| window == query)
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn insensitive_agrees_with_reference(
+        query in "[a-cA-CéÉİ]{0,3}",
+        contents in "[a-cA-CéÉßİ \r\n]{0,80}",
+    ) {
+        prop_assert_eq!(
+            reference_insensitive(&query, &contents),
+            search_insensitive(&query, &contents)
+        );
+    }
+
+    #[test]
+    fn insensitive_ignores_ascii_case(
+        query in "[a-zA-Z]{0,3}",
+        contents in "[a-zA-Z \n]{0,80}",
+    ) {
+        prop_assert_eq!(
+            search_insensitive(&query.to_ascii_lowercase(), &contents),
+            search_insensitive(&query.to_ascii_uppercase(), &contents)
+        );
+    }
+
+    #[test]
+    fn sensitive_matches_are_insensitive_matches(
+        query in "[a-cA-C]{0,3}",
+        contents in "[a-cA-C \n]{0,80}",
+    ) {
+        let insensitive = search_insensitive(&query, &contents);
+        for line in search(&query, &contents) {
+            prop_assert!(insensitive.contains(&line));
+        }
+    }
+
+    #[test]
+    fn spans_cover_the_query(
+        query in "[a-cA-CéÉİ]{1,3}",
+        contents in "[a-cA-CéÉßİ \n]{0,80}",
+    ) {
+        let matches = search_matches(&query, &contents, false);
+        let lines: Vec<&str> = matches.iter().map(|m| m.line).collect();
+        prop_assert_eq!(search_insensitive(&query, &contents), lines);
+
+        for m in &matches {
+            for span in &m.spans {
+                let found = m.line[span.clone()].to_lowercase();
+                prop_assert!(found.contains(&query.to_lowercase()));
+            }
+        }
+    }
+}
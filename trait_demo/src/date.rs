@@ -0,0 +1,265 @@
+// 只处理 UTC 的 Unix 时间戳（秒），不引入日期库
+
+/// 1970-01-01 之后的天数换算成 (年, 月, 日)，算法来自 Howard Hinnant 的 civil_from_days
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `civil_from_days` 的逆运算
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 闰年的 2 月有 29 天
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `to_utc` 的逆运算，超出范围的字段（包括 2 月 30 日这种）返回 `None`
+pub fn from_utc(
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+) -> Option<i64> {
+    let valid = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && hour < 24
+        && minute < 60
+        && second <= 60;
+    valid.then(|| {
+        days_from_civil(year, month, day) * 86_400 + (hour * 3600 + minute * 60 + second) as i64
+    })
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+/// 拆成 (年, 月, 日, 时, 分, 秒)
+pub fn to_utc(timestamp: i64) -> (i64, u32, u32, u32, u32, u32) {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86_400));
+    let secs = timestamp.rem_euclid(86_400) as u32;
+    (year, month, day, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// 形如 2024-05-01 12:30
+pub fn format_utc(timestamp: i64) -> String {
+    let (year, month, day, hour, minute, _) = to_utc(timestamp);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year, month, day, hour, minute
+    )
+}
+
+/// RSS 的 pubDate，形如 Wed, 01 May 2024 12:30:00 +0000
+pub fn format_rfc2822(timestamp: i64) -> String {
+    let (year, month, day, hour, minute, second) = to_utc(timestamp);
+    let weekday = WEEKDAYS[timestamp.div_euclid(86_400).rem_euclid(7) as usize];
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000",
+        weekday,
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        hour,
+        minute,
+        second
+    )
+}
+
+/// 星期可以省略，时区可以是 +hhmm 或 GMT、EST 这样的名字
+pub fn parse_rfc2822(text: &str) -> Option<i64> {
+    let mut fields: Vec<&str> = text.split_whitespace().collect();
+    if fields.first()?.ends_with(',') {
+        fields.remove(0);
+    }
+    let [day, month, year, time, zone] = fields.as_slice() else {
+        return None;
+    };
+
+    let month = MONTHS
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(month))? as u32
+        + 1;
+    let year: i64 = match year.parse().ok()? {
+        year @ 0..=49 => year + 2000,
+        year @ 50..=99 => year + 1900,
+        year => year,
+    };
+    let (hour, minute, second) = parse_time(time)?;
+    let offset = match *zone {
+        "GMT" | "UT" | "UTC" | "Z" => 0,
+        "EDT" => -4 * 60,
+        "EST" | "CDT" => -5 * 60,
+        "CST" | "MDT" => -6 * 60,
+        "MST" | "PDT" => -7 * 60,
+        "PST" => -8 * 60,
+        zone => parse_offset(zone, false)?,
+    };
+
+    let local = from_utc(year, month, day.parse().ok()?, hour, minute, second)?;
+    Some(local - offset * 60)
+}
+
+/// Atom 的 updated，形如 2024-05-01T12:30:00Z
+pub fn format_rfc3339(timestamp: i64) -> String {
+    let (year, month, day, hour, minute, second) = to_utc(timestamp);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// 小数秒会被舍去，时区是 Z 或 +hh:mm
+pub fn parse_rfc3339(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (date, rest) = text.split_at(text.find(['T', 't', ' '])?);
+    let rest = &rest[1..];
+
+    let mut date = date.splitn(3, '-');
+    let year = date.next()?.parse().ok()?;
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+
+    let zone_at = rest.find(['Z', 'z', '+', '-'])?;
+    let (time, zone) = rest.split_at(zone_at);
+    let time = time.split('.').next()?;
+    let (hour, minute, second) = parse_time(time)?;
+    let offset = match zone {
+        "Z" | "z" => 0,
+        zone => parse_offset(zone, true)?,
+    };
+
+    let local = from_utc(year, month, day, hour, minute, second)?;
+    Some(local - offset * 60)
+}
+
+/// HH:MM 或 HH:MM:SS
+fn parse_time(time: &str) -> Option<(u32, u32, u32)> {
+    let parts: Vec<u32> = time
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [hour, minute] => Some((*hour, *minute, 0)),
+        [hour, minute, second] => Some((*hour, *minute, *second)),
+        _ => None,
+    }
+}
+
+/// +hhmm（RFC 2822）或 +hh:mm（RFC 3339），返回分钟
+fn parse_offset(zone: &str, colon: bool) -> Option<i64> {
+    let (sign, digits) = match zone.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    let (hours, minutes) = if colon {
+        digits.split_once(':')?
+    } else if digits.len() == 4 {
+        digits.split_at(2)
+    } else {
+        return None;
+    };
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_to_dates() {
+        assert_eq!((1970, 1, 1), civil_from_days(0));
+        assert_eq!((2000, 2, 29), civil_from_days(11_016));
+        assert_eq!((1969, 12, 31), civil_from_days(-1));
+        assert_eq!((2024, 5, 1), civil_from_days(19_844));
+        for days in [-800_000, -1, 0, 59, 11_016, 19_844, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days, days_from_civil(year, month, day));
+        }
+    }
+
+    #[test]
+    fn month_lengths() {
+        assert_eq!(29, days_in_month(2024, 2));
+        assert_eq!(28, days_in_month(2023, 2));
+        assert_eq!(28, days_in_month(1900, 2));
+        assert_eq!(29, days_in_month(2000, 2));
+        assert_eq!(30, days_in_month(2024, 4));
+        assert_eq!(31, days_in_month(2024, 12));
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!("1970-01-01 00:00", format_utc(0));
+        assert_eq!("2024-05-01 12:30", format_utc(1_714_566_600));
+    }
+
+    #[test]
+    fn rfc2822() {
+        assert_eq!(
+            "Wed, 01 May 2024 12:30:00 +0000",
+            format_rfc2822(1_714_566_600)
+        );
+        assert_eq!("Thu, 01 Jan 1970 00:00:00 +0000", format_rfc2822(0));
+
+        let parse = parse_rfc2822;
+        assert_eq!(
+            Some(1_714_566_600),
+            parse("Wed, 01 May 2024 12:30:00 +0000")
+        );
+        assert_eq!(Some(1_714_566_600), parse("1 May 2024 12:30 GMT"));
+        assert_eq!(Some(1_714_566_600), parse("Wed, 01 May 2024 08:30:00 EDT"));
+        assert_eq!(Some(1_714_566_600), parse("Wed, 01 May 24 20:30:00 +0800"));
+        assert_eq!(None, parse("Wed, 01 Foo 2024 12:30:00 +0000"));
+        assert_eq!(None, parse("Wed, 01 May 2024 25:30:00 +0000"));
+        assert_eq!(None, parse("31 Feb 2024 12:30:00 GMT"));
+        assert_eq!(None, parse("29 Feb 2023 12:30:00 GMT"));
+        assert_eq!(None, parse("31 Apr 2024 12:30:00 GMT"));
+        assert!(parse("29 Feb 2024 12:30:00 GMT").is_some());
+        assert_eq!(None, parse("yesterday"));
+    }
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!("2024-05-01T12:30:00Z", format_rfc3339(1_714_566_600));
+
+        let parse = parse_rfc3339;
+        assert_eq!(Some(1_714_566_600), parse("2024-05-01T12:30:00Z"));
+        assert_eq!(Some(1_714_566_600), parse("2024-05-01T12:30:00.250Z"));
+        assert_eq!(Some(1_714_566_600), parse("2024-05-01T20:30:00+08:00"));
+        assert_eq!(Some(1_714_566_600), parse("2024-05-01T07:30:00-05:00"));
+        assert_eq!(None, parse("2024-05-01"));
+        assert_eq!(None, parse("2024-13-01T12:30:00Z"));
+        assert_eq!(None, parse("2024-02-30T12:30:00Z"));
+        assert_eq!(None, parse("1900-02-29T12:30:00Z"));
+        assert!(parse("2000-02-29T12:30:00Z").is_some());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::date;
+use crate::Summary;
+
+/// 摘要流：文章、推文以及以后新增的类型都可以放在一起
+#[derive(Default)]
+pub struct Feed {
+    items: Vec<Box<dyn Summary>>,
+}
+
+impl Feed {
+    pub fn new() -> Feed {
+        Feed { items: Vec::new() }
+    }
+
+    pub fn push(&mut self, item: impl Summary + 'static) {
+        self.items.push(Box::new(item));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &dyn Summary> {
+        self.items.iter().map(|item| item.as_ref())
+    }
+
+    pub fn get(&self, id: u64) -> Option<&dyn Summary> {
+        self.items().find(|item| item.id() == id)
+    }
+
+    /// 最新的排在前面，时间相同时按 id 排
+    pub fn sort_by_timestamp(&mut self) {
+        self.items
+            .sort_by_key(|item| (std::cmp::Reverse(item.timestamp()), item.id()));
+    }
+
+    /// 作者比较时忽略推特用户名前面的 `@`
+    pub fn by_author(&self, author: &str) -> Vec<&dyn Summary> {
+        let author = author.trim_start_matches('@');
+        self.items()
+            .filter(|item| item.summarize_author().trim_start_matches('@') == author)
+            .collect()
+    }
+
+    /// 第 `number` 页（从 1 开始，0 当作第 1 页），每页 `per_page` 条
+    pub fn page(&self, number: usize, per_page: usize) -> Page<'_> {
+        Page::new(self.items().collect(), number, per_page)
+    }
+}
+
+/// 一页摘要，也可以用过滤后的结果构造
+pub struct Page<'a> {
+    pub number: usize,
+    pub total_pages: usize,
+    pub items: Vec<&'a dyn Summary>,
+}
+
+impl<'a> Page<'a> {
+    pub fn new(items: Vec<&'a dyn Summary>, number: usize, per_page: usize) -> Page<'a> {
+        let number = number.max(1);
+        let per_page = per_page.max(1);
+        let total_pages = items.len().div_ceil(per_page);
+        let start = (number - 1).saturating_mul(per_page);
+        let items = items.into_iter().skip(start).take(per_page).collect();
+
+        Page {
+            number,
+            total_pages,
+            items,
+        }
+    }
+
+    /// 每条一行：时间、id 和截断到 `width` 个字符的摘要
+    pub fn digest(&self, width: usize) -> String {
+        let mut digest = format!("Page {}/{}\n", self.number, self.total_pages.max(1));
+        if self.items.is_empty() {
+            digest.push_str("No items\n");
+        }
+        for item in &self.items {
+            digest.push_str(&format!(
+                "{}  #{}  {}\n",
+                date::format_utc(item.timestamp()),
+                item.id(),
+                item.summarize_truncated(width)
+            ));
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_article as article, test_tweet as tweet};
+
+    // 以后新增的类型只要实现 Summary 就能放进 Feed
+    struct Podcast {
+        id: u64,
+        timestamp: i64,
+    }
+
+    impl Summary for Podcast {
+        fn id(&self) -> u64 {
+            self.id
+        }
+
+        fn timestamp(&self) -> i64 {
+            self.timestamp
+        }
+
+        fn summarize_author(&self) -> String {
+            String::from("podcast")
+        }
+    }
+
+    fn feed() -> Feed {
+        let mut feed = Feed::new();
+        feed.push(tweet(1, 300, "alice"));
+        feed.push(article(2, 100, "bob"));
+        feed.push(Podcast {
+            id: 3,
+            timestamp: 500,
+        });
+        feed.push(tweet(4, 300, "bob"));
+        feed.push(article(5, 200, "alice"));
+        feed
+    }
+
+    fn ids<'a>(items: impl IntoIterator<Item = &'a dyn Summary>) -> Vec<u64> {
+        items.into_iter().map(|item| item.id()).collect()
+    }
+
+    #[test]
+    fn sorts_newest_first() {
+        let mut feed = feed();
+        feed.sort_by_timestamp();
+
+        assert_eq!(vec![3, 1, 4, 5, 2], ids(feed.items()));
+        assert_eq!(5, feed.len());
+        assert_eq!("podcast", feed.get(3).unwrap().summarize_author());
+        assert!(feed.get(9).is_none());
+    }
+
+    #[test]
+    fn filters_by_author() {
+        let feed = feed();
+
+        assert_eq!(vec![1, 5], ids(feed.by_author("alice")));
+        assert_eq!(vec![2, 4], ids(feed.by_author("@bob")));
+        assert!(feed.by_author("carol").is_empty());
+    }
+
+    #[test]
+    fn paginates() {
+        let feed = feed();
+
+        let page = feed.page(2, 2);
+        assert_eq!((2, 3), (page.number, page.total_pages));
+        assert_eq!(vec![3, 4], ids(page.items));
+
+        let page = feed.page(3, 2);
+        assert_eq!((3, 3), (page.number, page.total_pages));
+        assert_eq!(vec![5], ids(page.items));
+
+        let page = feed.page(4, 2);
+        assert_eq!((4, 3), (page.number, page.total_pages));
+        assert!(page.items.is_empty());
+
+        let page = feed.page(0, 2);
+        assert_eq!((1, 3), (page.number, page.total_pages));
+        assert_eq!(vec![1, 2], ids(page.items));
+        assert!(feed.page(0, 2).digest(20).starts_with("Page 1/3\n"));
+
+        let page = Page::new(feed.by_author("bob"), 1, 10);
+        assert_eq!((1, 1), (page.number, page.total_pages));
+        assert_eq!(vec![2, 4], ids(page.items));
+    }
+
+    #[test]
+    fn renders_a_digest() {
+        let mut feed = feed();
+        feed.sort_by_timestamp();
+
+        assert_eq!(
+            "Page 1/3\n\
+             1970-01-01 00:08  #3  (Read more from pod…\n\
+             1970-01-01 00:05  #1  (Read more from @al…\n",
+            feed.page(1, 2).digest(20)
+        );
+        assert_eq!("Page 1/1\nNo items\n", Feed::new().page(1, 10).digest(20));
+    }
+}
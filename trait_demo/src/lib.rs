@@ -1,8 +1,70 @@
+mod date;
+mod feed;
+#[cfg(feature = "serde")]
+mod serialization;
+mod syndication;
+mod validate;
+
+pub use date::format_utc;
+pub use feed::{Feed, Page};
+#[cfg(feature = "serde")]
+pub use serialization::{from_json, from_toml, to_json, to_toml, FormatError};
+pub use syndication::{parse_atom, parse_feed, parse_rss, to_atom, to_rss, FeedError};
+pub use validate::{Validate, ValidationError};
+
 pub trait Summary {
-    fn summarize(&self) -> String;
+    fn id(&self) -> u64;
+
+    /// Unix 时间戳（秒）
+    fn timestamp(&self) -> i64;
+
+    fn summarize_author(&self) -> String;
+
+    // 默认实现，可以调用同一个 trait 里的其他方法
+    fn summarize(&self) -> String {
+        format!("(Read more from {}...)", self.summarize_author())
+    }
+
+    /// 导出成 RSS / Atom 时的标题
+    fn title(&self) -> String {
+        self.summarize()
+    }
+
+    /// 导出时的正文
+    fn body(&self) -> Option<String> {
+        None
+    }
+
+    /// 导出时的分类
+    fn category(&self) -> Option<String> {
+        None
+    }
+
+    /// 最多 `max_chars` 个字符的摘要，超出时以 `…` 结尾
+    fn summarize_truncated(&self, max_chars: usize) -> String {
+        truncate(&self.summarize(), max_chars)
+    }
 }
 
+/// 按字符（而不是字节）截断，保证不会切在多字节字符中间；省略号也算一个字符
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewsArticle {
+    pub id: u64,
+    pub timestamp: i64,
     pub headline: String,
     pub location: String,
     pub author: String,
@@ -16,22 +78,148 @@ impl NewsArticle {
     }
 }
 
-// 定义 trait
+// 定义 trait，覆盖默认的 summarize
 impl Summary for NewsArticle {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    fn summarize_author(&self) -> String {
+        self.author.clone()
+    }
+
     fn summarize(&self) -> String {
         format!("{}, by {} ({})", self.headline, self.author, self.location)
     }
+
+    fn title(&self) -> String {
+        self.headline.clone()
+    }
+
+    fn body(&self) -> Option<String> {
+        Some(self.content.clone())
+    }
+
+    // 导出时把地点放在分类里，导入时再取回来
+    fn category(&self) -> Option<String> {
+        Some(self.location.clone())
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tweet {
+    pub id: u64,
+    pub timestamp: i64,
     pub username: String,
     pub content: String,
     pub reply: bool,
     pub retweet: bool,
 }
 
+// 只实现 summarize_author，summarize 用默认实现
 impl Summary for Tweet {
-    fn summarize(&self) -> String {
-        format!("{}: {}", self.username, self.content)
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.username)
+    }
+
+    fn title(&self) -> String {
+        self.content.clone()
+    }
+}
+
+/// 各模块测试共用的文章，只有 id、时间和作者可变
+#[cfg(test)]
+pub(crate) fn test_article(id: u64, timestamp: i64, author: &str) -> NewsArticle {
+    NewsArticle {
+        id,
+        timestamp,
+        headline: String::from("Penguins win the Stanley Cup Championship!"),
+        location: String::from("Pittsburgh, PA, USA"),
+        author: String::from(author),
+        content: String::from(
+            "The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+        ),
+    }
+}
+
+/// 各模块测试共用的推文，只有 id、时间和用户名可变
+#[cfg(test)]
+pub(crate) fn test_tweet(id: u64, timestamp: i64, username: &str) -> Tweet {
+    Tweet {
+        id,
+        timestamp,
+        username: String::from(username),
+        content: String::from("of course, as you probably already know, people"),
+        reply: false,
+        retweet: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article() -> NewsArticle {
+        test_article(1, 1_714_566_600, "Iceburgh")
+    }
+
+    fn tweet() -> Tweet {
+        test_tweet(2, 1_714_570_200, "horse_ebooks")
+    }
+
+    #[test]
+    fn tweet_uses_default_summarize() {
+        let tweet = tweet();
+
+        assert_eq!("@horse_ebooks", tweet.summarize_author());
+        assert_eq!("(Read more from @horse_ebooks...)", tweet.summarize());
+    }
+
+    #[test]
+    fn article_overrides_summarize() {
+        let article = article();
+
+        assert_eq!("Iceburgh", article.summarize_author());
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship!, by Iceburgh (Pittsburgh, PA, USA)",
+            Summary::summarize(&article)
+        );
+        // 关联函数和 trait 方法同名时，用 NewsArticle::summarize 调用的是关联函数
+        assert_eq!(
+            "Iceburgh: The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            NewsArticle::summarize(article)
+        );
+    }
+
+    #[test]
+    fn truncated_summaries() {
+        assert_eq!(
+            "(Read more from @horse_ebooks...)",
+            tweet().summarize_truncated(100)
+        );
+        assert_eq!("(Read more…", tweet().summarize_truncated(11));
+        assert_eq!("Penguins…", article().summarize_truncated(9));
+        assert_eq!("", article().summarize_truncated(0));
+    }
+
+    #[test]
+    fn truncate_is_unicode_safe() {
+        assert_eq!("你好，世界", truncate("你好，世界", 5));
+        assert_eq!("你好，…", truncate("你好，世界！", 4));
+        assert_eq!("🦀…", truncate("🦀🦀🦀", 2));
+        assert_eq!("…", truncate("héllo", 1));
     }
 }
@@ -1,20 +1,67 @@
-use trait_demo::{NewsArticle, Summary, Tweet};
+use std::{env, fs, process};
+use trait_demo::{Feed, NewsArticle, Summary, Tweet};
 
 fn main() {
     let tweet = Tweet {
+        id: 1,
+        timestamp: 1_714_570_200,
         username: String::from("username"),
         content: String::from("content"),
         reply: false,
         retweet: false,
     };
-    println!("tweet: {}", tweet.summarize()); // tweet: username: content
+    println!("tweet: {}", tweet.summarize()); // tweet: (Read more from @username...)
+
+    // cargo run --features serde：不用手写结构体，也可以从 JSON / TOML 读进来
+    #[cfg(feature = "serde")]
+    {
+        let json = trait_demo::to_json(&tweet).unwrap();
+        let parsed: Tweet = trait_demo::from_json(&json).unwrap();
+        println!("tweet as TOML:\n{}", trait_demo::to_toml(&parsed).unwrap());
+    }
 
     let article = NewsArticle {
+        id: 2,
+        timestamp: 1_714_566_600,
         headline: String::from("headline"),
         location: String::from("location"),
         author: String::from("author"),
         content: String::from("content"),
     };
     println!("article {}", article.summarize()); // article headline, by author (location)
+    println!("article {}", article.summarize_truncated(10)); // article headline,…
     println!("article {}", NewsArticle::summarize(article)); // article author: content
+
+    let mut feed = Feed::new();
+    feed.push(tweet);
+    feed.push(NewsArticle {
+        id: 3,
+        timestamp: 1_714_563_000,
+        headline: String::from("another headline"),
+        location: String::from("location"),
+        author: String::from("author"),
+        content: String::from("content"),
+    });
+    feed.sort_by_timestamp();
+    // Page 1/1
+    // 2024-05-01 13:30  #1  (Read more from @username...)
+    // 2024-05-01 11:30  #3  another headline, by author (location)
+    print!("{}", feed.page(1, 10).digest(60));
+
+    // cargo run -- fixtures/rss.xml：把本地的 RSS / Atom 文件也加进来，再导出成 Atom
+    if let Some(path) = env::args().nth(1) {
+        let articles = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|xml| trait_demo::parse_feed(&xml).map_err(|err| err.to_string()))
+            .unwrap_or_else(|err| {
+                eprintln!("Problem reading {}: {}", path, err);
+                process::exit(1);
+            });
+        for article in articles {
+            feed.push(article);
+        }
+        feed.sort_by_timestamp();
+        print!("{}", feed.page(1, 10).digest(60));
+        print!("{}", trait_demo::to_atom("trait_demo", feed.items()));
+    }
 }
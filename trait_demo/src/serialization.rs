@@ -0,0 +1,148 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+use crate::{Validate, ValidationError};
+
+// 直接用 serde_json / toml 反序列化不会校验，要校验就用这里的 from_json / from_toml
+
+#[derive(Debug)]
+pub enum FormatError {
+    Json(serde_json::Error),
+    TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    /// 格式没问题，但内容没通过 `Validate`
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Json(err) => write!(f, "invalid JSON: {}", err),
+            FormatError::TomlDe(err) => write!(f, "invalid TOML: {}", err),
+            FormatError::TomlSer(err) => write!(f, "cannot write TOML: {}", err),
+            FormatError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FormatError::Json(err) => Some(err),
+            FormatError::TomlDe(err) => Some(err),
+            FormatError::TomlSer(err) => Some(err),
+            FormatError::Invalid(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(err: serde_json::Error) -> FormatError {
+        FormatError::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for FormatError {
+    fn from(err: toml::de::Error) -> FormatError {
+        FormatError::TomlDe(err)
+    }
+}
+
+impl From<toml::ser::Error> for FormatError {
+    fn from(err: toml::ser::Error) -> FormatError {
+        FormatError::TomlSer(err)
+    }
+}
+
+impl From<ValidationError> for FormatError {
+    fn from(err: ValidationError) -> FormatError {
+        FormatError::Invalid(err)
+    }
+}
+
+pub fn from_json<T: DeserializeOwned + Validate>(json: &str) -> Result<T, FormatError> {
+    let value: T = serde_json::from_str(json)?;
+    value.validate()?;
+    Ok(value)
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, FormatError> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+pub fn from_toml<T: DeserializeOwned + Validate>(text: &str) -> Result<T, FormatError> {
+    let value: T = toml::from_str(text)?;
+    value.validate()?;
+    Ok(value)
+}
+
+pub fn to_toml<T: Serialize>(value: &T) -> Result<String, FormatError> {
+    Ok(toml::to_string(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_article, test_tweet, NewsArticle, Tweet};
+
+    fn article() -> NewsArticle {
+        test_article(1, 1_714_566_600, "Iceburgh")
+    }
+
+    fn tweet() -> Tweet {
+        Tweet {
+            retweet: true,
+            ..test_tweet(2, 1_714_570_200, "horse_ebooks")
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = to_json(&article()).unwrap();
+        assert!(json.contains("\"headline\": \"Penguins win the Stanley Cup Championship!\""));
+        assert_eq!(article(), from_json::<NewsArticle>(&json).unwrap());
+
+        let json = to_json(&tweet()).unwrap();
+        assert_eq!(tweet(), from_json::<Tweet>(&json).unwrap());
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let text = to_toml(&tweet()).unwrap();
+        assert!(text.contains("username = \"horse_ebooks\"\n"));
+        assert_eq!(tweet(), from_toml::<Tweet>(&text).unwrap());
+
+        let text = to_toml(&article()).unwrap();
+        assert_eq!(article(), from_toml::<NewsArticle>(&text).unwrap());
+    }
+
+    #[test]
+    fn typed_errors() {
+        let json = r#"{"id": 1, "timestamp": 0, "headline": "", "location": "",
+                       "author": "a", "content": ""}"#;
+        assert!(matches!(
+            from_json::<NewsArticle>(json),
+            Err(FormatError::Invalid(ValidationError::EmptyHeadline))
+        ));
+
+        let text = "id = 1\ntimestamp = 0\nusername = \"@bad name\"\n\
+                    content = \"\"\nreply = false\nretweet = false\n";
+        match from_toml::<Tweet>(text) {
+            Err(FormatError::Invalid(ValidationError::InvalidUsername(name))) => {
+                assert_eq!("@bad name", name)
+            }
+            other => panic!("expected an invalid username, got {:?}", other),
+        }
+
+        assert!(matches!(
+            from_json::<Tweet>("{\"id\": 1}"),
+            Err(FormatError::Json(_))
+        ));
+        assert!(matches!(
+            from_toml::<NewsArticle>("id = "),
+            Err(FormatError::TomlDe(_))
+        ));
+    }
+}
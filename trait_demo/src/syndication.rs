@@ -0,0 +1,382 @@
+use roxmltree::{Document, Node};
+use std::error::Error;
+use std::fmt;
+
+use crate::date;
+use crate::{NewsArticle, Summary};
+
+// RSS 2.0 和 Atom 的导入导出。导入时：
+//   title -> headline，author / dc:creator / author>name -> author，
+//   content:encoded / description / content / summary -> content，category -> location，
+//   guid / id 末尾的数字 -> id（没有时按位置从 1 编号），pubDate / updated -> timestamp
+
+#[derive(Debug)]
+pub enum FeedError {
+    Xml(roxmltree::Error),
+    /// 根元素既不是 `<rss>` 也不是 `<feed>`
+    UnknownFormat(String),
+    /// 第 `entry` 个条目（从 1 开始）的日期无法解析
+    BadDate {
+        entry: usize,
+        date: String,
+    },
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeedError::Xml(err) => write!(f, "invalid XML: {}", err),
+            FeedError::UnknownFormat(root) => {
+                write!(f, "expected an RSS or Atom feed, found <{}>", root)
+            }
+            FeedError::BadDate { entry, date } => {
+                write!(f, "entry {} has an invalid date: {}", entry, date)
+            }
+        }
+    }
+}
+
+impl Error for FeedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FeedError::Xml(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<roxmltree::Error> for FeedError {
+    fn from(err: roxmltree::Error) -> FeedError {
+        FeedError::Xml(err)
+    }
+}
+
+/// 根据根元素判断是 RSS 还是 Atom
+pub fn parse_feed(xml: &str) -> Result<Vec<NewsArticle>, FeedError> {
+    let doc = Document::parse(xml)?;
+    match doc.root_element().tag_name().name() {
+        "rss" => rss_items(&doc),
+        "feed" => atom_entries(&doc),
+        other => Err(FeedError::UnknownFormat(other.to_string())),
+    }
+}
+
+pub fn parse_rss(xml: &str) -> Result<Vec<NewsArticle>, FeedError> {
+    let doc = Document::parse(xml)?;
+    match doc.root_element().tag_name().name() {
+        "rss" => rss_items(&doc),
+        other => Err(FeedError::UnknownFormat(other.to_string())),
+    }
+}
+
+pub fn parse_atom(xml: &str) -> Result<Vec<NewsArticle>, FeedError> {
+    let doc = Document::parse(xml)?;
+    match doc.root_element().tag_name().name() {
+        "feed" => atom_entries(&doc),
+        other => Err(FeedError::UnknownFormat(other.to_string())),
+    }
+}
+
+fn rss_items(doc: &Document) -> Result<Vec<NewsArticle>, FeedError> {
+    let items = doc
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("channel"))
+        .flat_map(|channel| channel.children())
+        .filter(|node| node.has_tag_name("item"));
+
+    items
+        .enumerate()
+        .map(|(i, item)| {
+            let timestamp = match child_text(item, "pubDate") {
+                Some(text) => date::parse_rfc2822(&text).ok_or(FeedError::BadDate {
+                    entry: i + 1,
+                    date: text,
+                })?,
+                None => 0,
+            };
+            // RSS 的 author 按规范是 "邮箱 (名字)"
+            let author = child_text(item, "creator")
+                .or_else(|| child_text(item, "author").map(|author| author_name(&author)));
+
+            Ok(NewsArticle {
+                id: child_text(item, "guid")
+                    .and_then(|guid| parse_id(&guid))
+                    .unwrap_or(i as u64 + 1),
+                timestamp,
+                headline: child_text(item, "title").unwrap_or_default(),
+                location: child_text(item, "category").unwrap_or_default(),
+                author: author.unwrap_or_default(),
+                content: child_text(item, "encoded")
+                    .or_else(|| child_text(item, "description"))
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn atom_entries(doc: &Document) -> Result<Vec<NewsArticle>, FeedError> {
+    let entries = doc
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("entry"));
+
+    entries
+        .enumerate()
+        .map(|(i, entry)| {
+            let timestamp = match child_text(entry, "updated").or(child_text(entry, "published")) {
+                Some(text) => date::parse_rfc3339(&text).ok_or(FeedError::BadDate {
+                    entry: i + 1,
+                    date: text,
+                })?,
+                None => 0,
+            };
+            let author = entry
+                .children()
+                .find(|node| node.has_tag_name("author"))
+                .and_then(|author| child_text(author, "name"));
+            let category = entry
+                .children()
+                .find(|node| node.has_tag_name("category"))
+                .and_then(|category| category.attribute("term"));
+
+            Ok(NewsArticle {
+                id: child_text(entry, "id")
+                    .and_then(|id| parse_id(&id))
+                    .unwrap_or(i as u64 + 1),
+                timestamp,
+                headline: child_text(entry, "title").unwrap_or_default(),
+                location: category.unwrap_or_default().to_string(),
+                author: author.unwrap_or_default(),
+                content: child_text(entry, "content")
+                    .or_else(|| child_text(entry, "summary"))
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// 第一个叫 `name` 的子元素的文本（不看命名空间前缀）
+fn child_text(node: Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+        .map(|child| {
+            child
+                .descendants()
+                .filter(|node| node.is_text())
+                .filter_map(|node| node.text())
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+}
+
+/// guid 和 Atom id 常常是 URL 或 URN，取最后一段里的数字
+fn parse_id(text: &str) -> Option<u64> {
+    text.rsplit([':', '/', '#', '=', '-']).next()?.parse().ok()
+}
+
+/// "jane@example.com (Jane Doe)" -> "Jane Doe"
+fn author_name(author: &str) -> String {
+    match (author.find('('), author.rfind(')')) {
+        (Some(start), Some(end)) if start < end => author[start + 1..end].trim().to_string(),
+        _ => author.trim().to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 导出成 RSS 2.0，作者写在 dc:creator 里，因为 RSS 的 author 要求是邮箱
+pub fn to_rss<'a>(title: &str, items: impl IntoIterator<Item = &'a dyn Summary>) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n  <channel>\n",
+    );
+    xml.push_str(&format!("    <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!(
+        "    <description>{}</description>\n",
+        escape(title)
+    ));
+
+    for item in items {
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!(
+            "      <guid isPermaLink=\"false\">{}</guid>\n",
+            item.id()
+        ));
+        xml.push_str(&format!("      <title>{}</title>\n", escape(&item.title())));
+        xml.push_str(&format!(
+            "      <dc:creator>{}</dc:creator>\n",
+            escape(&item.summarize_author())
+        ));
+        if let Some(category) = item.category().filter(|c| !c.is_empty()) {
+            xml.push_str(&format!(
+                "      <category>{}</category>\n",
+                escape(&category)
+            ));
+        }
+        if let Some(body) = item.body().filter(|b| !b.is_empty()) {
+            xml.push_str(&format!(
+                "      <description>{}</description>\n",
+                escape(&body)
+            ));
+        }
+        xml.push_str(&format!(
+            "      <pubDate>{}</pubDate>\n",
+            date::format_rfc2822(item.timestamp())
+        ));
+        xml.push_str("    </item>\n");
+    }
+
+    xml.push_str("  </channel>\n</rss>\n");
+    xml
+}
+
+/// 导出成 Atom，id 用 `urn:trait-demo:<id>`
+pub fn to_atom<'a>(title: &str, items: impl IntoIterator<Item = &'a dyn Summary>) -> String {
+    let items: Vec<&dyn Summary> = items.into_iter().collect();
+    let updated = items.iter().map(|item| item.timestamp()).max().unwrap_or(0);
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\">\n",
+    );
+    xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    xml.push_str("  <id>urn:trait-demo:feed</id>\n");
+    xml.push_str(&format!(
+        "  <updated>{}</updated>\n",
+        date::format_rfc3339(updated)
+    ));
+
+    for item in items {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>urn:trait-demo:{}</id>\n", item.id()));
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&item.title())));
+        xml.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape(&item.summarize_author())
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            date::format_rfc3339(item.timestamp())
+        ));
+        if let Some(category) = item.category().filter(|c| !c.is_empty()) {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", escape(&category)));
+        }
+        if let Some(body) = item.body().filter(|b| !b.is_empty()) {
+            xml.push_str(&format!(
+                "    <content type=\"text\">{}</content>\n",
+                escape(&body)
+            ));
+        }
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_article, test_tweet};
+
+    const RSS: &str = include_str!("../fixtures/rss.xml");
+    const ATOM: &str = include_str!("../fixtures/atom.xml");
+
+    fn summaries(articles: &[NewsArticle]) -> Vec<&dyn Summary> {
+        articles.iter().map(|a| a as &dyn Summary).collect()
+    }
+
+    #[test]
+    fn parses_rss() {
+        let articles = parse_rss(RSS).unwrap();
+
+        assert_eq!(3, articles.len());
+        assert_eq!(test_article(42, 1_714_566_600, "Iceburgh"), articles[0]);
+        // author 里的邮箱被去掉，CDATA 和实体都还原成文本
+        assert_eq!("Jane Doe", articles[1].author);
+        assert_eq!("<b>Rust</b> & Ferris", articles[1].content);
+        assert_eq!(1_714_600_800, articles[1].timestamp);
+        // 没有 guid 和日期的条目
+        assert_eq!((3, 0), (articles[2].id, articles[2].timestamp));
+        assert_eq!("", articles[2].location);
+    }
+
+    #[test]
+    fn parses_atom() {
+        let articles = parse_atom(ATOM).unwrap();
+
+        assert_eq!(2, articles.len());
+        assert_eq!(7, articles[0].id);
+        assert_eq!("Atom is a feed format", articles[0].headline);
+        assert_eq!("Mark", articles[0].author);
+        assert_eq!("Internet", articles[0].location);
+        assert_eq!(1_714_566_600, articles[0].timestamp);
+        assert_eq!("Only a summary here", articles[1].content);
+        assert_eq!(1_714_570_200, articles[1].timestamp);
+    }
+
+    #[test]
+    fn rss_round_trip() {
+        let articles = parse_feed(RSS).unwrap();
+        let exported = to_rss("Sports", summaries(&articles));
+
+        assert_eq!(include_str!("../fixtures/rss.export.xml"), exported);
+        assert_eq!(articles, parse_feed(&exported).unwrap());
+    }
+
+    #[test]
+    fn atom_round_trip() {
+        let articles = parse_feed(ATOM).unwrap();
+        let exported = to_atom("Example", summaries(&articles));
+
+        assert_eq!(include_str!("../fixtures/atom.export.xml"), exported);
+        assert_eq!(articles, parse_feed(&exported).unwrap());
+
+        // RSS 导入的文章也能导出成 Atom
+        let articles = parse_rss(RSS).unwrap();
+        let atom = to_atom("Sports", summaries(&articles));
+        assert_eq!(articles, parse_atom(&atom).unwrap());
+    }
+
+    #[test]
+    fn exports_any_summary() {
+        let tweet = test_tweet(9, 0, "horse_ebooks");
+        let rss = to_rss("Tweets", [&tweet as &dyn Summary]);
+
+        let article = &parse_rss(&rss).unwrap()[0];
+        assert_eq!(9, article.id);
+        assert_eq!(tweet.content, article.headline);
+        assert_eq!("@horse_ebooks", article.author);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(parse_feed("<rss>"), Err(FeedError::Xml(_))));
+        assert!(matches!(
+            parse_feed("<html/>"),
+            Err(FeedError::UnknownFormat(root)) if root == "html"
+        ));
+        assert!(matches!(parse_atom(RSS), Err(FeedError::UnknownFormat(_))));
+
+        let bad = "<rss><channel><item><pubDate>someday</pubDate></item></channel></rss>";
+        assert_eq!(
+            "entry 1 has an invalid date: someday",
+            parse_rss(bad).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{NewsArticle, Tweet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    EmptyHeadline,
+    /// 用户名只能是 1 到 15 个字母、数字或下划线，不带 `@`
+    InvalidUsername(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::EmptyHeadline => write!(f, "headline must not be empty"),
+            ValidationError::InvalidUsername(username) => write!(
+                f,
+                "invalid username '{}': use 1 to 15 letters, digits or underscores",
+                username
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl Validate for NewsArticle {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.headline.trim().is_empty() {
+            return Err(ValidationError::EmptyHeadline);
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Tweet {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let valid = (1..=15).contains(&self.username.len())
+            && self
+                .username
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if !valid {
+            return Err(ValidationError::InvalidUsername(self.username.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_article, test_tweet};
+
+    fn tweet(username: &str) -> Tweet {
+        test_tweet(1, 0, username)
+    }
+
+    #[test]
+    fn usernames() {
+        assert_eq!(Ok(()), tweet("horse_ebooks").validate());
+        assert_eq!(Ok(()), tweet("a").validate());
+        for bad in ["", "@horse_ebooks", "has space", "sixteen_chars_xx", "名字"] {
+            assert_eq!(
+                Err(ValidationError::InvalidUsername(String::from(bad))),
+                tweet(bad).validate()
+            );
+        }
+    }
+
+    #[test]
+    fn headlines() {
+        let mut article = test_article(1, 0, "author");
+        assert_eq!(Ok(()), article.validate());
+
+        article.headline = String::from("  ");
+        assert_eq!(Err(ValidationError::EmptyHeadline), article.validate());
+        assert_eq!(
+            "headline must not be empty",
+            article.validate().unwrap_err().to_string()
+        );
+    }
+}